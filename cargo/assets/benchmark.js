@@ -0,0 +1,84 @@
+// 40,000 bouncing foxes, run it with `export * from "./benchmark.js";` as the whole main.js
+const SPRITES = 40000;
+const SIZE = 20;
+
+let width = 0;
+let height = 0;
+let camera = null;
+let batch = null;
+let fox = null;
+
+// one world unit per physical pixel, origin in the top left corner
+function fit(newWidth, newHeight) {
+  width = newWidth;
+  height = newHeight;
+  camera.resize(width, height, width, height);
+  camera.setPosition(width / 2, height / 2);
+}
+
+export function start(gl) {
+  const [, , viewportWidth, viewportHeight] = gl.getParameter(gl.VIEWPORT);
+  batch = new PolygonBatch(gl);
+  batch.setYDown(true);
+  camera = new OrthoCamera(viewportWidth, viewportHeight, viewportWidth, viewportHeight);
+  camera.setYDown(true);
+  fit(viewportWidth, viewportHeight);
+  fox = new Texture(gl, "fox.png");
+  gl.clearColor(0.5, 0.8, 0.2, 1);
+
+  const sprites = [];
+  for (let i = 0; i < SPRITES; i++) {
+    sprites.push({
+      x: Math.random() * width,
+      y: Math.random() * height,
+      speedX: Math.random() * width - width / 2,
+      speedY: Math.random() * height - height / 2,
+    });
+  }
+
+  let accumulate = 0;
+  let frames = 0;
+  return (delta) => {
+    accumulate += delta;
+    frames++;
+    if (accumulate >= 1) {
+      print(`fps: ${frames}`);
+      accumulate = 0;
+      frames = 0;
+    }
+
+    gl.clear(gl.COLOR_BUFFER_BIT);
+    batch.setProjection(camera.getCombined());
+    batch.begin();
+    for (const sprite of sprites) {
+      sprite.x += sprite.speedX * delta;
+      sprite.y += sprite.speedY * delta;
+      if (sprite.x >= width) {
+        sprite.x = width;
+        sprite.speedX = -sprite.speedX;
+      } else if (sprite.x <= 0) {
+        sprite.x = 0;
+        sprite.speedX = -sprite.speedX;
+      }
+      if (sprite.y >= height) {
+        sprite.y = height;
+        sprite.speedY = -sprite.speedY;
+      } else if (sprite.y <= 0) {
+        sprite.y = 0;
+        sprite.speedY = -sprite.speedY;
+      }
+      batch.draw(fox, sprite.x - SIZE / 2, sprite.y - SIZE / 2, SIZE, SIZE);
+    }
+    batch.end();
+  };
+}
+
+// the backend has already reset the viewport
+export function resize(size) {
+  fit(size.physicalWidth, size.physicalHeight);
+}
+
+export function dispose() {
+  batch.dispose();
+  fox.dispose();
+}
@@ -0,0 +1 @@
+export const BACKGROUND = [1.0, 0.2, 0.4, 1.0];
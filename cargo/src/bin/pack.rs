@@ -0,0 +1,99 @@
+use std::{fs, path::Path, process};
+
+use my_game::gdx::g2d::texture_packer::{PackStrategy, PackerSettings, TexturePacker};
+
+const USAGE: &str = "usage: pack <output dir> <atlas name> <image or dir>... \
+[--max-size n] [--padding n] [--bleed n] [--pot] [--skyline] [--pma]";
+
+fn fail(message: &str) -> ! {
+  eprintln!("{}", message);
+  process::exit(1);
+}
+
+fn number(value: Option<String>, flag: &str) -> u32 {
+  match value.as_deref().map(str::parse) {
+    Some(Ok(number)) => number,
+    _ => fail(&format!("{} needs a number\n{}", flag, USAGE)),
+  }
+}
+
+// images are packed in the order given, the files of a directory sorted by name
+fn add(packer: &mut TexturePacker, path: &Path) {
+  if path.is_dir() {
+    let mut entries: Vec<_> = match fs::read_dir(path) {
+      Ok(entries) => entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .collect(),
+      Err(error) => fail(&format!("{}: {}", path.display(), error)),
+    };
+    entries.sort();
+    for entry in entries {
+      if entry.is_file() && image::ImageFormat::from_path(&entry).is_ok() {
+        add(packer, &entry);
+      }
+    }
+    return;
+  }
+  match packer.try_add_file(path.to_string_lossy().into_owned()) {
+    Ok(()) => (),
+    Err(error) => fail(&error.to_string()),
+  }
+}
+
+// writes <name>.atlas and its pages for `TextureAtlas::load_from_file`
+pub fn main() {
+  let mut settings = PackerSettings::default();
+  let mut paths = vec![];
+  let mut args = std::env::args().skip(1);
+  while let Some(arg) = args.next() {
+    match arg.as_str() {
+      "--max-size" => {
+        let size = number(args.next(), &arg);
+        settings.max_width = size;
+        settings.max_height = size;
+      }
+      "--padding" => settings.padding = number(args.next(), &arg),
+      "--bleed" => settings.bleed = number(args.next(), &arg),
+      "--pot" => settings.power_of_two = true,
+      "--skyline" => settings.strategy = PackStrategy::Skyline,
+      "--pma" => settings.premultiply_alpha = true,
+      "-h" | "--help" => {
+        println!("{}", USAGE);
+        return;
+      }
+      _ if arg.starts_with("--") => fail(&format!("unknown option {}\n{}", arg, USAGE)),
+      _ => paths.push(arg),
+    }
+  }
+  if paths.len() < 3 {
+    fail(USAGE);
+  }
+  let output = Path::new(&paths[0]);
+  let name = &paths[1];
+
+  let mut packer = TexturePacker::new(settings);
+  for path in &paths[2..] {
+    add(&mut packer, Path::new(path));
+  }
+  let atlas = match packer.pack(name) {
+    Ok(atlas) => atlas,
+    Err(error) => fail(&error.to_string()),
+  };
+  match fs::create_dir_all(output) {
+    Ok(()) => (),
+    Err(error) => fail(&format!("{}: {}", output.display(), error)),
+  }
+  match atlas.write(output) {
+    Ok(()) => (),
+    Err(error) => fail(&error.to_string()),
+  }
+  for page in &atlas.data.pages {
+    println!("{} {}x{}", page.texture_file, page.width, page.height);
+  }
+  println!(
+    "{} regions on {} pages",
+    atlas.data.regions.len(),
+    atlas.data.pages.len()
+  );
+}
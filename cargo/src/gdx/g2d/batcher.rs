@@ -9,23 +9,82 @@ fn incr(i: &mut usize) -> usize {
 }
 
 const QUAD_TRIANGLES: [u16; 6] = [0, 1, 2, 2, 3, 0];
-
-use crate::gdx::misc::color::Color;
+// what `create` uses
+pub const DEFAULT_MAX_VERTICES: usize = 10920;
+// the sampler array of MULTI_TEXTURED_FS, the units GLES2 guarantees
+pub const MAX_BATCH_TEXTURES: usize = 8;
+// floats per vertex passed to `draw_vertices`, position, the color packed with
+// `Color::to_float_bits` and texcoords
+pub const VERTEX_SIZE: usize = 5;
+
+use crate::gdx::{
+  math::{affine2::Affine2, mat4::Mat4},
+  misc::color::Color,
+};
 
 use super::{
-  mesh::{Mesh, VertexAttribute},
-  shader_program::ShaderProgram,
+  error::GdxError,
+  mesh::{supports_uint_indices, Mesh, VertexAttribute},
+  shader_program::{
+    ShaderProgram, COLORED_TEXTURED_FS, COLORED_TEXTURED_VS, MULTI_TEXTURED_FS, MULTI_TEXTURED_VS,
+  },
   texture::Texture,
+  texture_atlas::AtlasRegion,
+  texture_region::TextureRegion,
 };
 
+// how `draw_with_options` places a quad around its origin and which part of the texture it
+// shows, the default is the whole texture unrotated at scale 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DrawOptions {
+  pub origin_x: f32,
+  pub origin_y: f32,
+  pub rotation: f32,
+  pub scale_x: f32,
+  pub scale_y: f32,
+  // u1, v1 is the bottom left corner
+  pub u1: f32,
+  pub v1: f32,
+  pub u2: f32,
+  pub v2: f32,
+  // the texture area is turned 90 degrees, as packers store some regions
+  pub rotate: bool,
+}
+
+impl Default for DrawOptions {
+  fn default() -> Self {
+    Self {
+      origin_x: 0.,
+      origin_y: 0.,
+      rotation: 0.,
+      scale_x: 1.,
+      scale_y: 1.,
+      u1: 0.,
+      v1: 1.,
+      u2: 1.,
+      v2: 0.,
+      rotate: false,
+    }
+  }
+}
+
 pub struct PolygonBatch {
   pub context: Rc<Context>,
   pub is_drawing: bool,
   mesh: Mesh,
   shader: ShaderProgram,
-  last_texture: Option<Rc<Texture>>,
+  // drawn with instead of `shader` when set, owned by the caller
+  custom_shader: Option<Rc<ShaderProgram>>,
+  // the textures of the pending vertices, the position is the unit each is bound to
+  textures: Vec<Rc<Texture>>,
+  max_textures: usize,
   vertices_length: usize,
   indices_length: usize,
+  // the index buffer holds QUAD_TRIANGLES for every quad that fits, flushes of nothing but
+  // quads draw with it as is instead of uploading indices
+  quad_indices: bool,
+  // something other than a quad was drawn since the last flush
+  custom_indices: bool,
 
   y_down: bool,
 
@@ -37,46 +96,105 @@ pub struct PolygonBatch {
   color: Color,
 
   projection_values: [f32; 16],
+  // applied before the projection, the stack keeps what `push_transform` replaced
+  transform: Mat4,
+  transform_stack: Vec<[f32; 16]>,
+  // projection * transform, what the shader gets
+  combined: Mat4,
 
-  vertices: [f32; 32],
+  vertices: [f32; VERTEX_SIZE * 4],
   draw_calls: i32,
 }
 
 impl PolygonBatch {
   pub fn create(context: &Rc<Context>) -> Self {
-    Self::new(context, 10920)
+    Self::new(context, DEFAULT_MAX_VERTICES)
   }
+  // panics if the shader doesn't compile, see `try_new`
   pub fn new(context: &Rc<Context>, max_vertices: usize) -> Self {
-    // TODO: draw calls
-    let shader = ShaderProgram::colored_textured(&context);
-    let mesh = Mesh::new(
+    match Self::try_new(context, max_vertices) {
+      Ok(batch) => batch,
+      Err(error) => panic!("{}", error),
+    }
+  }
+  pub fn try_new(context: &Rc<Context>, max_vertices: usize) -> Result<Self, GdxError> {
+    Self::try_new_with_textures(context, max_vertices, 1)
+  }
+  // panics if the shader doesn't compile, see `try_new_with_textures`
+  pub fn new_with_textures(
+    context: &Rc<Context>,
+    max_vertices: usize,
+    max_textures: usize,
+  ) -> Self {
+    match Self::try_new_with_textures(context, max_vertices, max_textures) {
+      Ok(batch) => batch,
+      Err(error) => panic!("{}", error),
+    }
+  }
+  // up to `max_textures` textures are bound to their own units and drawn together, each vertex
+  // carries the index of its unit, capped at MAX_BATCH_TEXTURES and what the driver supports,
+  // 1 switches textures with a flush like before
+  pub fn try_new_with_textures(
+    context: &Rc<Context>,
+    max_vertices: usize,
+    max_textures: usize,
+  ) -> Result<Self, GdxError> {
+    let units = unsafe { context.get_parameter_i32(MAX_TEXTURE_IMAGE_UNITS) } as usize;
+    let max_textures = max_textures.min(MAX_BATCH_TEXTURES).min(units).max(1);
+    // indices past 65535 need UNSIGNED_INT, without it the batch stays at what u16 addresses
+    let index_type = match max_vertices > 65536 && supports_uint_indices(context) {
+      true => UNSIGNED_INT,
+      false => UNSIGNED_SHORT,
+    };
+    let max_vertices = match index_type {
+      UNSIGNED_INT => max_vertices,
+      _ => max_vertices.min(65536),
+    };
+    let mut attributes = vec![
+      VertexAttribute::position2(),
+      VertexAttribute::color(),
+      VertexAttribute::texcoords(),
+    ];
+    let shader = match max_textures {
+      1 => ShaderProgram::try_new(&context, COLORED_TEXTURED_VS, COLORED_TEXTURED_FS)?,
+      _ => {
+        attributes.push(VertexAttribute::texture_index());
+        ShaderProgram::try_new(&context, MULTI_TEXTURED_VS, MULTI_TEXTURED_FS)?
+      }
+    };
+    let mut mesh = Mesh::new_with_usage(
       &context,
-      vec![
-        VertexAttribute::position2(),
-        VertexAttribute::color(),
-        VertexAttribute::texcoords(),
-      ],
+      attributes,
       max_vertices,
       max_vertices * 3,
+      STREAM_DRAW,
     );
-    Self {
+    mesh.index_type = index_type;
+    Ok(Self {
       context: Rc::clone(context),
       is_drawing: false,
       y_down: true,
       mesh,
       shader,
-      last_texture: None,
+      custom_shader: None,
+      textures: vec![],
+      max_textures,
+      quad_indices: false,
+      custom_indices: false,
       vertices_length: 0,
       indices_length: 0,
       projection_values: [0.0; 16],
+      transform: Mat4::new(),
+      transform_stack: vec![],
+      combined: Mat4::new(),
       src_color_blend: SRC_ALPHA,
       src_alpha_blend: SRC_ALPHA,
       dst_color_blend: ONE_MINUS_SRC_ALPHA,
       dst_alpha_blend: ONE_MINUS_SRC_ALPHA,
       color: Color::WHITE,
-      vertices: [0.0; 32],
+      vertices: [0.0; VERTEX_SIZE * 4],
       draw_calls: 0,
-    }
+    })
   }
 
   pub fn set_color(&mut self, color: &Color) {
@@ -111,34 +229,164 @@ impl PolygonBatch {
     self.dst_alpha_blend = dst_alpha;
   }
 
+  // while drawing, what is pending is flushed with the old matrices first
   pub fn set_projection(&mut self, projection: &[f32; 16]) {
+    if self.is_drawing {
+      self.flush();
+    }
     for i in 0..16 {
       self.projection_values[i] = projection[i];
     }
+    if self.is_drawing {
+      self.set_matrices();
+    }
+  }
+
+  // moves, rotates or scales everything drawn after it, identity by default
+  pub fn set_transform(&mut self, transform: &[f32; 16]) {
+    if self.is_drawing {
+      self.flush();
+    }
+    self.transform.set(transform);
+    if self.is_drawing {
+      self.set_matrices();
+    }
+  }
+
+  pub fn get_transform(&self) -> [f32; 16] {
+    self.transform.values
+  }
+
+  // the transform becomes current * `transform` until the matching `pop_transform`, so
+  // nested groups of sprites move with their parents
+  pub fn push_transform(&mut self, transform: &[f32; 16]) {
+    if self.is_drawing {
+      self.flush();
+    }
+    self.transform_stack.push(self.transform.values);
+    let mut matrix = Mat4::new();
+    matrix.set(transform);
+    self.transform.multiply(&matrix);
+    if self.is_drawing {
+      self.set_matrices();
+    }
+  }
+
+  pub fn pop_transform(&mut self) {
+    let transform = match self.transform_stack.pop() {
+      Some(transform) => transform,
+      None => panic!("PolygonBatch has no pushed transform to pop"),
+    };
+    if self.is_drawing {
+      self.flush();
+    }
+    self.transform.set(&transform);
+    if self.is_drawing {
+      self.set_matrices();
+    }
+  }
+
+  // how many transforms were pushed and not popped yet
+  pub fn get_transform_depth(&self) -> usize {
+    self.transform_stack.len()
+  }
+
+  fn set_matrices(&mut self) {
+    self
+      .combined
+      .set(&self.projection_values)
+      .multiply(&self.transform);
+    // a uniform the compiler optimized out has nothing to set
+    let _ = self
+      .current_shader()
+      .set_uniform_4x4f(ShaderProgram::MVP_MATRIX, &self.combined.values);
+  }
+
+  // binds the current shader and sets the uniforms the batch owns
+  fn setup_shader(&mut self) {
+    self.current_shader().bind();
+    self.set_matrices();
+    let shader = self.current_shader();
+    // a uniform the compiler optimized out has nothing to set
+    let _ = match self.max_textures {
+      1 => shader.set_uniform_i(ShaderProgram::SAMPLER, 0),
+      _ => {
+        let units: Vec<i32> = (0..MAX_BATCH_TEXTURES as i32).collect();
+        let name = format!("{}s", ShaderProgram::SAMPLER);
+        shader.set_uniform_iv(&name, &units)
+      }
+    };
+  }
+
+  fn current_shader(&self) -> &ShaderProgram {
+    match &self.custom_shader {
+      Some(shader) => shader,
+      None => &self.shader,
+    }
+  }
+
+  // panics if the shader has no position attribute, see `try_set_shader`
+  pub fn set_shader(&mut self, shader: Option<Rc<ShaderProgram>>) {
+    match self.try_set_shader(shader) {
+      Ok(()) => (),
+      Err(error) => panic!("{}", error),
+    }
+  }
+
+  // draws with `shader` until it's set back to None, the batch's own shader. the vertices
+  // have the attributes of COLORED_TEXTURED_VS, or MULTI_TEXTURED_VS with more than one
+  // texture, of which only the position has to be active, a shader that ignores the color
+  // or the texcoords gets them left out. the projection and samplers are set like the
+  // batch's own. while drawing, what is pending is flushed first and the new shader is
+  // bound, so its uniforms can be set right after, a uniform changed later on needs a
+  // `flush` before it to leave the sprites before it alone
+  pub fn try_set_shader(&mut self, shader: Option<Rc<ShaderProgram>>) -> Result<(), GdxError> {
+    if let Some(shader) = &shader {
+      if shader
+        .get_attribute_location(ShaderProgram::POSITION)
+        .is_none()
+      {
+        return Err(GdxError::MissingAttribute(
+          ShaderProgram::POSITION.to_string(),
+        ));
+      }
+    }
+    if self.is_drawing {
+      self.flush();
+    }
+    self.custom_shader = shader;
+    if self.is_drawing {
+      self.setup_shader();
+    }
+    Ok(())
+  }
+
+  pub fn get_shader(&self) -> Option<Rc<ShaderProgram>> {
+    self.custom_shader.clone()
   }
 
   pub fn get_draw_calls(&self) -> i32 {
     self.draw_calls
   }
 
+  pub fn get_max_textures(&self) -> usize {
+    self.max_textures
+  }
+
   pub fn begin(&mut self) {
     if self.is_drawing {
       panic!("PolygonBatch is already drawing");
     }
-    self.last_texture = None;
     self.is_drawing = true;
     self.draw_calls = 0;
 
     self.vertices_length = 0;
     self.indices_length = 0;
+    self.custom_indices = false;
 
-    self.last_texture = None;
+    self.textures.clear();
 
-    self.shader.bind();
-    self
-      .shader
-      .set_uniform_4x4f(ShaderProgram::MVP_MATRIX, &self.projection_values);
-    self.shader.set_uniform_i("u_texture", 0);
+    self.setup_shader();
 
     unsafe {
       self.context.enable(BLEND);
@@ -159,8 +407,8 @@ impl PolygonBatch {
       self.flush();
     }
 
-    self.shader.unbind();
-    self.last_texture = None;
+    self.current_shader().unbind();
+    self.textures.clear();
     self.is_drawing = false;
 
     unsafe {
@@ -173,18 +421,79 @@ impl PolygonBatch {
       return;
     }
 
-    match &self.last_texture {
-      Some(texture) => {
-        texture.bind();
-        self.mesh.draw(&self.shader, TRIANGLES);
-        self.draw_calls += 1;
+    if self.textures.is_empty() {
+      return;
+    }
+    for (unit, texture) in self.textures.iter().enumerate() {
+      texture.bind_to(unit as u32);
+    }
+    // other code binds textures without picking a unit
+    unsafe {
+      self.context.active_texture(TEXTURE0);
+    }
+    match self.custom_indices {
+      true => {
+        self.mesh.set_indices_length(self.indices_length);
+        self.quad_indices = false;
+      }
+      false => {
+        if !self.quad_indices {
+          self.upload_quad_indices();
+        }
+        // already in the index buffer, only the count changes
+        self.mesh.indices_length = self.indices_length;
+        self.mesh.dirty_indices = false;
+      }
+    }
+    let shader = match &self.custom_shader {
+      Some(shader) => shader.as_ref(),
+      None => &self.shader,
+    };
+    // inactive attributes are left out of the mesh's VAO, only a lost context gets here
+    match self.mesh.draw(shader, TRIANGLES) {
+      Ok(_) => (),
+      Err(error) => panic!("{}", error),
+    }
+    self.draw_calls += 1;
+
+    self.vertices_length = 0;
+    self.indices_length = 0;
+    self.custom_indices = false;
+    self.mesh.set_vertices_length(0);
+    self.textures.clear();
+  }
+
+  fn upload_quad_indices(&mut self) {
+    let max_vertices = self.mesh.vertices.len() / self.mesh.elements_per_vertex;
+    let quads = (self.mesh.indices.len() / QUAD_TRIANGLES.len()).min(max_vertices / 4);
+    for quad in 0..quads {
+      for (i, index) in QUAD_TRIANGLES.iter().enumerate() {
+        self.mesh.indices[quad * QUAD_TRIANGLES.len() + i] = *index as u32 + quad as u32 * 4;
+      }
+    }
+    self.mesh.set_indices_length(quads * QUAD_TRIANGLES.len());
+    match self.mesh.update() {
+      Ok(()) => (),
+      Err(error) => panic!("{}", error),
+    }
+    self.quad_indices = true;
+  }
 
-        self.vertices_length = 0;
-        self.indices_length = 0;
-        self.mesh.set_indices_length(0);
-        self.mesh.set_vertices_length(0);
+  // the unit `texture` is drawn from, a texture that doesn't fit flushes the pending ones
+  fn texture_unit(&mut self, texture: &Rc<Texture>) -> usize {
+    match self
+      .textures
+      .iter()
+      .position(|bound| bound.as_ref() == texture.as_ref())
+    {
+      Some(unit) => unit,
+      None => {
+        if self.textures.len() == self.max_textures {
+          self.flush();
+        }
+        self.textures.push(Rc::clone(texture));
+        self.textures.len() - 1
       }
-      None => {}
     }
   }
 
@@ -193,71 +502,152 @@ impl PolygonBatch {
     self.shader.dispose();
   }
 
+  // `vertices` holds VERTEX_SIZE floats per vertex and `indices` triangles into them, the
+  // texture index is added here, a submission bigger than the whole batch is split into
+  // triangles drawn over several flushes. panics if an index is past the last vertex or the
+  // indices aren't whole triangles, like drawing outside `begin` and `end` does
   pub fn draw_vertices_with_indices(
     &mut self,
     texture: &Rc<Texture>,
     vertices: &[f32],
     indices: &[u16],
   ) {
-    if self.last_texture.is_none() || self.last_texture.as_deref().unwrap() != texture.as_ref() {
-      self.flush();
-      self.last_texture = Some(Rc::clone(texture));
-    } else if self.vertices_length + vertices.len() > self.mesh.vertices.len()
+    self.add_vertices(texture, vertices, indices)
+  }
+
+  // for meshes with more than 65536 vertices
+  pub fn draw_vertices_with_indices_u32(
+    &mut self,
+    texture: &Rc<Texture>,
+    vertices: &[f32],
+    indices: &[u32],
+  ) {
+    self.add_vertices(texture, vertices, indices)
+  }
+
+  fn add_vertices<I: Copy + Into<u32>>(
+    &mut self,
+    texture: &Rc<Texture>,
+    vertices: &[f32],
+    indices: &[I],
+  ) {
+    if !indices.len().is_multiple_of(3) {
+      panic!(
+        "PolygonBatch draws triangles, {} indices is not a multiple of 3",
+        indices.len()
+      );
+    }
+    let num_vertices = vertices.len() / VERTEX_SIZE;
+    if let Some(index) = indices
+      .iter()
+      .map(|index| (*index).into())
+      .find(|index| *index as usize >= num_vertices)
+    {
+      panic!(
+        "PolygonBatch index {} is out of range for {} vertices",
+        index, num_vertices
+      );
+    }
+    let vertex_size = self.mesh.elements_per_vertex;
+    let length = vertices.len() / VERTEX_SIZE * vertex_size;
+    if length > self.mesh.vertices.len() || indices.len() > self.mesh.indices.len() {
+      return self.draw_split(texture, vertices, indices);
+    }
+    if self.vertices_length + length > self.mesh.vertices.len()
       || self.indices_length + indices.len() > self.mesh.indices.len()
     {
       self.flush();
     }
-
-    let index_start = self.mesh.num_vertices();
-    for i in 0..vertices.len() {
-      self.mesh.vertices[self.vertices_length + i] = vertices[i];
+    let unit = self.texture_unit(texture);
+    let quad = vertices.len() == VERTEX_SIZE * 4
+      && indices.len() == QUAD_TRIANGLES.len()
+      && indices
+        .iter()
+        .zip(QUAD_TRIANGLES)
+        .all(|(index, quad_index)| (*index).into() == quad_index as u32);
+    if !quad {
+      self.custom_indices = true;
     }
-    self.vertices_length += vertices.len();
-    self.mesh.set_vertices_length(self.vertices_length);
 
-    for i in 0..indices.len() {
-      self.mesh.indices[self.indices_length + i] = indices[i] + index_start as u16;
+    let index_start = self.mesh.num_vertices() as u32;
+    self.write_vertices(vertices, unit);
+    for (i, index) in indices.iter().enumerate() {
+      self.mesh.indices[self.indices_length + i] = (*index).into() + index_start;
     }
     self.indices_length += indices.len();
-    self.mesh.set_indices_length(self.indices_length);
-  }
-
-  pub fn draw_vertices(&mut self, texture: &Rc<Texture>, vertices: &[f32]) {
-    self.draw_vertices_with_indices(texture, vertices, &QUAD_TRIANGLES)
   }
 
-  pub fn draw_own_vertices(&mut self, texture: &Rc<Texture>) {
-    if self.last_texture.is_none() || self.last_texture.as_deref().unwrap() != texture.as_ref() {
-      self.flush();
-      self.last_texture = Some(Rc::clone(texture));
-    } else if self.vertices_length + self.vertices.len() > self.mesh.vertices.len()
-      || self.indices_length + QUAD_TRIANGLES.len() > self.mesh.indices.len()
-    {
-      self.flush();
+  // one triangle at a time, the vertices a triangle uses are copied the first time they
+  // are needed after each flush, `add_vertices` checked the indices
+  fn draw_split<I: Copy + Into<u32>>(
+    &mut self,
+    texture: &Rc<Texture>,
+    vertices: &[f32],
+    indices: &[I],
+  ) {
+    let vertex_size = self.mesh.elements_per_vertex;
+    // where each of `vertices` is in the pending ones, u32::MAX until copied
+    let mut copied = vec![u32::MAX; vertices.len() / VERTEX_SIZE];
+    let mut unit = self.texture_unit(texture);
+    for triangle in indices.chunks_exact(3) {
+      let new_vertices = triangle
+        .iter()
+        .filter(|index| copied[(**index).into() as usize] == u32::MAX)
+        .count();
+      if self.vertices_length + new_vertices * vertex_size > self.mesh.vertices.len()
+        || self.indices_length + triangle.len() > self.mesh.indices.len()
+      {
+        self.flush();
+        copied.fill(u32::MAX);
+        unit = self.texture_unit(texture);
+      }
+      self.custom_indices = true;
+      for index in triangle {
+        let index = (*index).into() as usize;
+        if copied[index] == u32::MAX {
+          copied[index] = self.mesh.num_vertices() as u32;
+          self.write_vertices(
+            &vertices[index * VERTEX_SIZE..(index + 1) * VERTEX_SIZE],
+            unit,
+          );
+        }
+        self.mesh.indices[self.indices_length] = copied[index];
+        self.indices_length += 1;
+      }
     }
+  }
 
-    let vertices = &self.vertices;
-    let indices = &QUAD_TRIANGLES;
-    let index_start = self.mesh.num_vertices();
-    for i in 0..vertices.len() {
-      self.mesh.vertices[self.vertices_length + i] = vertices[i];
+  fn write_vertices(&mut self, vertices: &[f32], unit: usize) {
+    let vertex_size = self.mesh.elements_per_vertex;
+    let length = vertices.len() / VERTEX_SIZE * vertex_size;
+    match self.max_textures {
+      1 => {
+        self.mesh.vertices[self.vertices_length..self.vertices_length + length]
+          .copy_from_slice(&vertices[..length]);
+      }
+      _ => {
+        for (i, vertex) in vertices.chunks_exact(VERTEX_SIZE).enumerate() {
+          let start = self.vertices_length + i * vertex_size;
+          self.mesh.vertices[start..start + VERTEX_SIZE].copy_from_slice(vertex);
+          self.mesh.vertices[start + VERTEX_SIZE] = unit as f32;
+        }
+      }
     }
-    self.vertices_length += vertices.len();
+    self.vertices_length += length;
     self.mesh.set_vertices_length(self.vertices_length);
+  }
 
-    for i in 0..indices.len() {
-      self.mesh.indices[self.indices_length + i] = indices[i] + index_start as u16;
-    }
-    self.indices_length += indices.len();
-    self.mesh.set_indices_length(self.indices_length);
+  pub fn draw_vertices(&mut self, texture: &Rc<Texture>, vertices: &[f32]) {
+    self.draw_vertices_with_indices(texture, vertices, &QUAD_TRIANGLES)
   }
 
-  // TODO: affine2 and draw_transformed
+  pub fn draw_own_vertices(&mut self, texture: &Rc<Texture>) {
+    let vertices = self.vertices;
+    self.draw_vertices_with_indices(texture, &vertices, &QUAD_TRIANGLES)
+  }
 
   pub fn draw(&mut self, texture: &Rc<Texture>, x: f32, y: f32, width: f32, height: f32) {
-    self.draw_with_options(
-      texture, x, y, width, height, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0, 1.0, 1.0, 0.0, false,
-    )
+    self.draw_with_options(texture, x, y, width, height, &DrawOptions::default())
   }
 
   pub fn draw_with_rot_and_scl(
@@ -273,9 +663,94 @@ impl PolygonBatch {
     scale_x: f32,
     scale_y: f32,
   ) {
-    self.draw_with_options(
-      texture, x, y, width, height, origin_x, origin_y, rotation, scale_x, scale_y, 0.0, 1.0, 1.0,
-      0.0, false,
+    let options = DrawOptions {
+      origin_x,
+      origin_y,
+      rotation,
+      scale_x,
+      scale_y,
+      ..DrawOptions::default()
+    };
+    self.draw_with_options(texture, x, y, width, height, &options)
+  }
+
+  // at the region's own size in pixels
+  pub fn draw_region(&mut self, region: &TextureRegion, x: f32, y: f32) {
+    self.draw_region_with_size(
+      region,
+      x,
+      y,
+      region.get_width() as f32,
+      region.get_height() as f32,
+    )
+  }
+
+  pub fn draw_region_with_size(
+    &mut self,
+    region: &TextureRegion,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+  ) {
+    self.draw_region_with_options(region, x, y, width, height, &DrawOptions::default())
+  }
+
+  // the texture area of `options` is replaced by the region's
+  pub fn draw_region_with_options(
+    &mut self,
+    region: &TextureRegion,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    options: &DrawOptions,
+  ) {
+    // v is the region's top, `draw_with_options` takes the bottom first
+    let options = DrawOptions {
+      u1: region.u,
+      v1: region.v2,
+      u2: region.u2,
+      v2: region.v,
+      rotate: region.rotated,
+      ..*options
+    };
+    self.draw_with_options(&region.texture, x, y, width, height, &options)
+  }
+
+  // x, y, width and height are those of the original image, before the packer stripped
+  // its whitespace, so trimmed frames of an animation stay in place
+  pub fn draw_atlas_region(&mut self, region: &AtlasRegion, x: f32, y: f32) {
+    self.draw_atlas_region_with_size(
+      region,
+      x,
+      y,
+      region.original_width as f32,
+      region.original_height as f32,
+    )
+  }
+
+  pub fn draw_atlas_region_with_size(
+    &mut self,
+    region: &AtlasRegion,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+  ) {
+    let scale_x = width / region.original_width as f32;
+    let scale_y = height / region.original_height as f32;
+    // the offset counts from the bottom of the original image
+    let offset_y = match self.y_down {
+      true => region.original_height as f32 - region.packed_height as f32 - region.offset_y,
+      false => region.offset_y,
+    };
+    self.draw_region_with_size(
+      &region.region,
+      x + region.offset_x * scale_x,
+      y + offset_y * scale_y,
+      region.packed_width as f32 * scale_x,
+      region.packed_height as f32 * scale_y,
     )
   }
 
@@ -286,24 +761,20 @@ impl PolygonBatch {
     y: f32,
     width: f32,
     height: f32,
-    origin_x: f32,
-    origin_y: f32,
-    rotation: f32,
-    scale_x: f32,
-    scale_y: f32,
-    ou1: f32,
-    ov1: f32,
-    ou2: f32,
-    ov2: f32,
-    rotate: bool,
+    options: &DrawOptions,
   ) {
-    let mut ov1 = ov1;
-    let mut ov2 = ov2;
-    if self.y_down {
-      let tmp_v1 = ov1;
-      ov1 = ov2;
-      ov2 = tmp_v1;
-    }
+    let DrawOptions {
+      origin_x,
+      origin_y,
+      rotation,
+      scale_x,
+      scale_y,
+      u1,
+      v1,
+      u2,
+      v2,
+      rotate,
+    } = *options;
     // TODO: no width/height provided case
     let mut x1 = -origin_x;
     let mut x2 = width - origin_x;
@@ -316,17 +787,17 @@ impl PolygonBatch {
     let mut y4 = height - origin_y;
 
     if scale_x != 1. {
-      x1 = x1 * scale_x;
-      x2 = x2 * scale_x;
-      x3 = x3 * scale_x;
-      x4 = x4 * scale_x;
+      x1 *= scale_x;
+      x2 *= scale_x;
+      x3 *= scale_x;
+      x4 *= scale_x;
     }
 
     if scale_y != 1. {
-      y1 = y1 * scale_y;
-      y2 = y2 * scale_y;
-      y3 = y3 * scale_y;
-      y4 = y4 * scale_y;
+      y1 *= scale_y;
+      y2 *= scale_y;
+      y3 *= scale_y;
+      y4 *= scale_y;
     }
 
     if rotation != 0. {
@@ -366,6 +837,55 @@ impl PolygonBatch {
     y3 += y + origin_y;
     y4 += y + origin_y;
 
+    self.draw_quad(
+      texture,
+      [x1, x2, x3, x4],
+      [y1, y2, y3, y4],
+      [u1, v1, u2, v2],
+      rotate,
+    )
+  }
+
+  // the given region drawn width by height, its bottom left corner at the transform's origin
+  pub fn draw_transformed(
+    &mut self,
+    region: &TextureRegion,
+    width: f32,
+    height: f32,
+    transform: &Affine2,
+  ) {
+    let x1 = transform.m02;
+    let y1 = transform.m12;
+    let x2 = transform.m00 * width + transform.m02;
+    let y2 = transform.m10 * width + transform.m12;
+    let x3 = transform.m00 * width + transform.m01 * height + transform.m02;
+    let y3 = transform.m10 * width + transform.m11 * height + transform.m12;
+    let x4 = transform.m01 * height + transform.m02;
+    let y4 = transform.m11 * height + transform.m12;
+
+    self.draw_quad(
+      &region.texture,
+      [x1, x2, x3, x4],
+      [y1, y2, y3, y4],
+      [region.u, region.v2, region.u2, region.v],
+      region.rotated,
+    )
+  }
+
+  // corners go counter-clockwise from the bottom left, texcoords are u, v of the bottom left
+  // then u2, v2 of the top right
+  fn draw_quad(
+    &mut self,
+    texture: &Rc<Texture>,
+    [x1, x2, x3, x4]: [f32; 4],
+    [y1, y2, y3, y4]: [f32; 4],
+    [ou1, mut ov1, ou2, mut ov2]: [f32; 4],
+    rotate: bool,
+  ) {
+    if self.y_down {
+      std::mem::swap(&mut ov1, &mut ov2);
+    }
+
     let mut u1 = ou1;
     let mut v1 = ov1;
     let mut u2 = ou2;
@@ -400,41 +920,29 @@ impl PolygonBatch {
     let quad = &mut self.vertices;
     let mut i: usize = 0;
 
-    let color = &self.color;
+    let color = self.color.to_float_bits();
 
     quad[incr(&mut i)] = x1;
     quad[incr(&mut i)] = y1;
-    quad[incr(&mut i)] = color.r;
-    quad[incr(&mut i)] = color.g;
-    quad[incr(&mut i)] = color.b;
-    quad[incr(&mut i)] = color.a;
+    quad[incr(&mut i)] = color;
     quad[incr(&mut i)] = u1;
     quad[incr(&mut i)] = v1;
 
     quad[incr(&mut i)] = x2;
     quad[incr(&mut i)] = y2;
-    quad[incr(&mut i)] = color.r;
-    quad[incr(&mut i)] = color.g;
-    quad[incr(&mut i)] = color.b;
-    quad[incr(&mut i)] = color.a;
+    quad[incr(&mut i)] = color;
     quad[incr(&mut i)] = u3;
     quad[incr(&mut i)] = v3;
 
     quad[incr(&mut i)] = x3;
     quad[incr(&mut i)] = y3;
-    quad[incr(&mut i)] = color.r;
-    quad[incr(&mut i)] = color.g;
-    quad[incr(&mut i)] = color.b;
-    quad[incr(&mut i)] = color.a;
+    quad[incr(&mut i)] = color;
     quad[incr(&mut i)] = u2;
     quad[incr(&mut i)] = v2;
 
     quad[incr(&mut i)] = x4;
     quad[incr(&mut i)] = y4;
-    quad[incr(&mut i)] = color.r;
-    quad[incr(&mut i)] = color.g;
-    quad[incr(&mut i)] = color.b;
-    quad[incr(&mut i)] = color.a;
+    quad[incr(&mut i)] = color;
     quad[incr(&mut i)] = u4;
     quad[incr(&mut i)] = v4;
 
@@ -0,0 +1,102 @@
+use std::{error::Error, fmt};
+
+// what the fallible `try_*` constructors and setters of g2d fail with, the plain versions
+// panic with the same message
+#[derive(Debug, Clone, PartialEq)]
+pub enum GdxError {
+  // a text file like an atlas couldn't be read
+  Io {
+    path: String,
+    message: String,
+  },
+  // `line` counts from 1
+  Parse {
+    path: String,
+    line: usize,
+    message: String,
+  },
+  // the image file is missing or couldn't be decoded
+  Image {
+    path: String,
+    message: String,
+  },
+  // a file format or color type the image crate has no decoder for
+  UnsupportedFormat {
+    path: String,
+    message: String,
+  },
+  // `stage` is "vertex" or "fragment", `log` the driver's info log
+  ShaderCompile {
+    stage: &'static str,
+    log: String,
+  },
+  ProgramLink {
+    log: String,
+  },
+  // the name isn't in the program, or the compiler optimized it out
+  MissingUniform(String),
+  MissingAttribute(String),
+  // an image given to the texture packer that doesn't fit on a page even alone,
+  // the sizes include padding and bleed
+  ImageTooLarge {
+    name: String,
+    width: u32,
+    height: u32,
+    max_width: u32,
+    max_height: u32,
+  },
+  // pixel data shorter than its size and format need, `length` is in bytes
+  ImageDataSize {
+    width: u32,
+    height: u32,
+    length: usize,
+  },
+  // the driver couldn't create an object, usually a lost context
+  Gl(String),
+}
+
+impl fmt::Display for GdxError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      GdxError::Io { path, message } => write!(f, "{}: {}", path, message),
+      GdxError::Parse {
+        path,
+        line,
+        message,
+      } => write!(f, "{}:{}: {}", path, line, message),
+      GdxError::Image { path, message } => write!(f, "{}: {}", path, message),
+      GdxError::UnsupportedFormat { path, message } => write!(f, "{}: {}", path, message),
+      GdxError::ShaderCompile { stage, log } => {
+        write!(f, "{} shader failed to compile: {}", stage, log.trim_end())
+      }
+      GdxError::ProgramLink { log } => {
+        write!(f, "shader program failed to link: {}", log.trim_end())
+      }
+      GdxError::MissingUniform(name) => write!(f, "no active uniform named {}", name),
+      GdxError::MissingAttribute(name) => write!(f, "no active attribute named {}", name),
+      GdxError::ImageTooLarge {
+        name,
+        width,
+        height,
+        max_width,
+        max_height,
+      } => write!(
+        f,
+        "{} is {}x{}, pages are at most {}x{}",
+        name, width, height, max_width, max_height
+      ),
+      GdxError::ImageDataSize {
+        width,
+        height,
+        length,
+      } => write!(
+        f,
+        "{} bytes are too few for a {}x{} image",
+        length, width, height
+      ),
+      GdxError::Gl(message) => write!(f, "{}", message),
+    }
+  }
+}
+
+impl Error for GdxError {}
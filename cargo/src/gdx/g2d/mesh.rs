@@ -2,12 +2,14 @@ use std::rc::Rc;
 
 use glow::*;
 
-use super::shader_program::ShaderProgram;
+use super::{error::GdxError, shader_program::ShaderProgram};
 
 pub struct VertexAttribute {
   pub name: &'static str,
   pub vertex_type: u32,
   pub num_elements: usize,
+  // integer types read as 0..1 (-1..1 for signed ones) instead of their value
+  pub normalized: bool,
 }
 
 impl VertexAttribute {
@@ -16,6 +18,13 @@ impl VertexAttribute {
       name,
       vertex_type,
       num_elements,
+      normalized: false,
+    }
+  }
+  pub fn new_normalized(name: &'static str, vertex_type: u32, num_elements: usize) -> Self {
+    Self {
+      normalized: true,
+      ..Self::new(name, vertex_type, num_elements)
     }
   }
   pub fn position2() -> Self {
@@ -27,13 +36,30 @@ impl VertexAttribute {
   pub fn texcoords() -> Self {
     VertexAttribute::new(ShaderProgram::TEXCOORDS, FLOAT, 2)
   }
+  // 4 bytes packed into one float of the vertex array, see `Color::to_float_bits`
   pub fn color() -> Self {
-    // TODO: use u8 for this
+    VertexAttribute::new_normalized(ShaderProgram::COLOR, UNSIGNED_BYTE, 4)
+  }
+  pub fn color_unpacked() -> Self {
     VertexAttribute::new(ShaderProgram::COLOR, FLOAT, 4)
   }
   pub fn color2() -> Self {
     VertexAttribute::new(ShaderProgram::COLOR2, FLOAT, 4)
   }
+  // which of the batch's texture units a vertex samples
+  pub fn texture_index() -> Self {
+    VertexAttribute::new(ShaderProgram::TEXTURE_INDEX, FLOAT, 1)
+  }
+  // rounded up to 4 bytes so every attribute starts aligned, the vertex array is f32 and
+  // smaller types share one of its elements
+  pub fn size_in_bytes(&self) -> usize {
+    let type_size = match self.vertex_type {
+      BYTE | UNSIGNED_BYTE => 1,
+      SHORT | UNSIGNED_SHORT => 2,
+      _ => 4,
+    };
+    (self.num_elements * type_size).div_ceil(4) * 4
+  }
 }
 
 pub struct Mesh {
@@ -42,13 +68,47 @@ pub struct Mesh {
   pub vertices_buffer: Option<Buffer>,
   pub vertices_length: usize,
   pub dirty_vertices: bool,
-  pub indices: Vec<u16>,
+  // kept as u32, narrowed on upload for UNSIGNED_SHORT
+  pub indices: Vec<u32>,
   pub indices_buffer: Option<Buffer>,
   pub indices_length: usize,
   pub dirty_indices: bool,
+  // f32 elements of `vertices` per vertex, not attribute components
   pub elements_per_vertex: usize,
   pub attributes: Vec<VertexAttribute>,
-  pub vao: Option<VertexArray>,
+  // one per program the mesh was drawn with, attribute locations differ between programs
+  // so switching shaders only switches VAOs
+  pub vaos: Vec<(Program, VertexArray)>,
+  // STATIC_DRAW, DYNAMIC_DRAW or STREAM_DRAW for both buffers
+  pub usage: u32,
+  // UNSIGNED_SHORT, or UNSIGNED_INT for more than 65536 vertices where
+  // `supports_uint_indices`
+  pub index_type: u32,
+  short_indices: Vec<u16>,
+}
+
+// GL 3, GLES 3 and WebGL 2 have 32 bit indices, GLES 2 and WebGL 1 only with an extension
+pub fn supports_uint_indices(gl: &Context) -> bool {
+  let version = gl.version();
+  let extensions = gl.supported_extensions();
+  !version.is_embedded
+    || version.major >= 3
+    || extensions.contains("GL_OES_element_index_uint")
+    || extensions.contains("OES_element_index_uint")
+}
+
+// static buffers are sized to their data on every update, the others always get all of
+// `capacity`, a same sized `buffer_data` orphans the old storage so the driver can recycle
+// a free block instead of reallocating or waiting for draws still reading it, then only
+// what's used is written with `buffer_sub_data`
+unsafe fn upload(gl: &Context, target: u32, data: &[u8], capacity: usize, usage: u32) {
+  match usage {
+    STATIC_DRAW => gl.buffer_data_u8_slice(target, data, usage),
+    _ => {
+      gl.buffer_data_size(target, capacity as i32, usage);
+      gl.buffer_sub_data_u8_slice(target, 0, data);
+    }
+  }
 }
 
 impl Mesh {
@@ -57,10 +117,21 @@ impl Mesh {
     attributes: Vec<VertexAttribute>,
     max_vertices: usize,
     max_indices: usize,
+  ) -> Self {
+    Self::new_with_usage(context, attributes, max_vertices, max_indices, STATIC_DRAW)
+  }
+
+  // DYNAMIC_DRAW or STREAM_DRAW for meshes rewritten every frame, like a batch's
+  pub fn new_with_usage(
+    context: &Rc<Context>,
+    attributes: Vec<VertexAttribute>,
+    max_vertices: usize,
+    max_indices: usize,
+    usage: u32,
   ) -> Self {
     let mut elements_per_vertex = 0;
     for attribute in &attributes {
-      elements_per_vertex += attribute.num_elements;
+      elements_per_vertex += attribute.size_in_bytes() / core::mem::size_of::<f32>();
     }
     let vertices = vec![0.0; max_vertices * elements_per_vertex];
 
@@ -76,7 +147,10 @@ impl Mesh {
       dirty_indices: false,
       elements_per_vertex,
       attributes,
-      vao: None,
+      vaos: vec![],
+      usage,
+      index_type: UNSIGNED_SHORT,
+      short_indices: vec![],
     }
   }
 
@@ -101,12 +175,19 @@ impl Mesh {
   pub fn set_indices(&mut self, indices: &[u16]) {
     self.indices_length = indices.len();
     for i in 0..indices.len() {
-      self.indices[i] = indices[i];
+      self.indices[i] = indices[i] as u32;
     }
     self.dirty_indices = true;
   }
 
-  pub fn draw(&mut self, shader: &ShaderProgram, primitive_type: u32) -> &Self {
+  // needs `index_type` UNSIGNED_INT for indices past 65535
+  pub fn set_indices_u32(&mut self, indices: &[u32]) {
+    self.indices_length = indices.len();
+    self.indices[..indices.len()].copy_from_slice(indices);
+    self.dirty_indices = true;
+  }
+
+  pub fn draw(&mut self, shader: &ShaderProgram, primitive_type: u32) -> Result<&Self, GdxError> {
     self.draw_with_offset(
       shader,
       primitive_type,
@@ -116,42 +197,52 @@ impl Mesh {
       } else {
         self.vertices_length / self.elements_per_vertex
       },
-    );
-    self
+    )?;
+    Ok(self)
   }
 
   pub fn num_vertices(&self) -> usize {
     self.vertices_length / self.elements_per_vertex
   }
 
+  // `offset` counts indices, or vertices for a mesh without indices
   pub fn draw_with_offset(
     &mut self,
     shader: &ShaderProgram,
     primitive_type: u32,
     offset: usize,
     count: usize,
-  ) -> &Self {
+  ) -> Result<&Self, GdxError> {
     if self.dirty_indices || self.dirty_vertices {
-      self.update();
+      self.update()?;
     }
-    self.bind(shader);
+    self.bind(shader)?;
     let gl = &self.context;
     unsafe {
       if self.indices_length > 0 {
-        gl.draw_elements(primitive_type, count as i32, UNSIGNED_SHORT, offset as i32);
+        let index_size = match self.index_type {
+          UNSIGNED_INT => core::mem::size_of::<u32>(),
+          _ => core::mem::size_of::<u16>(),
+        };
+        gl.draw_elements(
+          primitive_type,
+          count as i32,
+          self.index_type,
+          (offset * index_size) as i32,
+        );
       } else {
         gl.draw_arrays(primitive_type, offset as i32, count as i32);
       }
     }
-    self
+    Ok(self)
   }
 
-  pub fn update(&mut self) {
+  pub fn update(&mut self) -> Result<(), GdxError> {
     let gl = &self.context;
     if self.dirty_vertices {
       if self.vertices_buffer.is_none() {
         unsafe {
-          self.vertices_buffer = Some(gl.create_buffer().unwrap());
+          self.vertices_buffer = Some(gl.create_buffer().map_err(GdxError::Gl)?);
         }
       }
       unsafe {
@@ -160,7 +251,13 @@ impl Mesh {
           self.vertices_length * core::mem::size_of::<f32>(),
         );
         gl.bind_buffer(ARRAY_BUFFER, self.vertices_buffer);
-        gl.buffer_data_u8_slice(ARRAY_BUFFER, vertices_u8, STATIC_DRAW);
+        upload(
+          gl,
+          ARRAY_BUFFER,
+          vertices_u8,
+          self.vertices.len() * core::mem::size_of::<f32>(),
+          self.usage,
+        );
         self.dirty_vertices = false;
       };
     }
@@ -168,59 +265,93 @@ impl Mesh {
     if self.dirty_indices {
       if self.indices_buffer.is_none() {
         unsafe {
-          self.indices_buffer = Some(gl.create_buffer().unwrap());
+          self.indices_buffer = Some(gl.create_buffer().map_err(GdxError::Gl)?);
         }
       }
       unsafe {
-        let indices_u8: &[u8] = core::slice::from_raw_parts(
-          self.indices.as_ptr() as *const u8,
-          self.indices_length * core::mem::size_of::<u16>(),
-        );
+        let (indices_u8, capacity): (&[u8], usize) = match self.index_type {
+          UNSIGNED_INT => (
+            core::slice::from_raw_parts(
+              self.indices.as_ptr() as *const u8,
+              self.indices_length * core::mem::size_of::<u32>(),
+            ),
+            self.indices.len() * core::mem::size_of::<u32>(),
+          ),
+          _ => {
+            self.short_indices.clear();
+            self.short_indices.extend(
+              self.indices[..self.indices_length]
+                .iter()
+                .map(|index| *index as u16),
+            );
+            (
+              core::slice::from_raw_parts(
+                self.short_indices.as_ptr() as *const u8,
+                self.indices_length * core::mem::size_of::<u16>(),
+              ),
+              self.indices.len() * core::mem::size_of::<u16>(),
+            )
+          }
+        };
         gl.bind_buffer(ELEMENT_ARRAY_BUFFER, self.indices_buffer);
-        gl.buffer_data_u8_slice(ELEMENT_ARRAY_BUFFER, indices_u8, STATIC_DRAW);
+        upload(gl, ELEMENT_ARRAY_BUFFER, indices_u8, capacity, self.usage);
         self.dirty_indices = false;
       };
     }
+    Ok(())
   }
 
-  pub fn bind(&mut self, shader: &ShaderProgram) {
+  // the vertex layout is recorded in the program's VAO the first time, an attribute that
+  // isn't active in `shader`, unused or optimized out by the compiler, is left out like
+  // libGDX does
+  pub fn bind(&mut self, shader: &ShaderProgram) -> Result<(), GdxError> {
     let gl = &self.context;
     unsafe {
       gl.bind_buffer(ARRAY_BUFFER, self.vertices_buffer);
-      if self.vao.is_none() {
-        self.vao = Some(gl.create_vertex_array().unwrap());
-        let mut offset = 0;
-        let vao = self.vao.unwrap();
-        gl.bind_vertex_array(Some(vao));
-        for attribute in &self.attributes {
-          let location = shader.get_attribute_location(attribute.name).unwrap();
-          gl.enable_vertex_attrib_array(location);
-          gl.vertex_attrib_pointer_f32(
-            location,
-            attribute.num_elements as i32,
-            attribute.vertex_type,
-            false,
-            self.elements_per_vertex as i32 * core::mem::size_of::<f32>() as i32,
-            offset as i32 * core::mem::size_of::<f32>() as i32,
-          );
-          offset += attribute.num_elements;
+      let known = self
+        .vaos
+        .iter()
+        .find(|(program, _)| *program == shader.program);
+      match known {
+        Some((_, vao)) => gl.bind_vertex_array(Some(*vao)),
+        None => {
+          let vao = gl.create_vertex_array().map_err(GdxError::Gl)?;
+          self.vaos.push((shader.program, vao));
+          let mut offset = 0;
+          gl.bind_vertex_array(Some(vao));
+          for attribute in &self.attributes {
+            if let Some(location) = shader.get_attribute_location(attribute.name) {
+              gl.enable_vertex_attrib_array(location);
+              gl.vertex_attrib_pointer_f32(
+                location,
+                attribute.num_elements as i32,
+                attribute.vertex_type,
+                attribute.normalized,
+                self.elements_per_vertex as i32 * core::mem::size_of::<f32>() as i32,
+                offset as i32,
+              );
+            }
+            offset += attribute.size_in_bytes();
+          }
         }
-      } else {
-        gl.bind_vertex_array(Some(self.vao.unwrap()));
       }
 
       if self.indices_length > 0 {
         gl.bind_buffer(ELEMENT_ARRAY_BUFFER, self.indices_buffer);
       }
     }
+    Ok(())
   }
 
   pub fn unbind(&self, shader: &ShaderProgram) {
     let gl = &self.context;
     unsafe {
+      // `bind` failed before enabling anything if one is missing
       for attribute in &self.attributes {
-        let location = shader.get_attribute_location(&attribute.name).unwrap();
-        gl.disable_vertex_attrib_array(location);
+        match shader.get_attribute_location(&attribute.name) {
+          Some(location) => gl.disable_vertex_attrib_array(location),
+          None => (),
+        }
       }
       gl.bind_buffer(ARRAY_BUFFER, None);
       if self.indices_length > 0 {
@@ -239,6 +370,9 @@ impl Mesh {
         Some(buffer) => self.context.delete_buffer(buffer),
         None => (),
       }
+      for (_, vao) in self.vaos.drain(..) {
+        self.context.delete_vertex_array(vao);
+      }
       self.vertices_buffer = None;
       self.indices_buffer = None;
     }
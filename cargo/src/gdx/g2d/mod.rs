@@ -1,5 +1,9 @@
 pub mod batcher;
+pub mod error;
 pub mod mesh;
 pub mod ortho_cam;
 pub mod shader_program;
 pub mod texture;
+pub mod texture_atlas;
+pub mod texture_packer;
+pub mod texture_region;
@@ -158,9 +158,11 @@ impl OrthoCamera {
     world_coords
   }
 
+  // screen y points down like pointer positions, normalized device y points up
   pub fn unproject_vec2<'a>(&self, world_coord: &'a mut Vec2, screen_coord: &Vec2) -> &'a mut Vec2 {
-    let mut vec3 = Vec3::new(screen_coord.x, screen_coord.y, 0.0);
+    let mut vec3 = Vec3::new(screen_coord.x, self.screen_height - screen_coord.y, -1.0);
     Self::screen_to_world(&mut vec3, self.screen_width, self.screen_height);
+    Mat4::mat4_proj(&self.inverse_projection_view.values, &mut vec3);
     world_coord.set(vec3.x, vec3.y)
   }
 
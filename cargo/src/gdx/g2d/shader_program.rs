@@ -1,8 +1,16 @@
-use std::rc::Rc;
+use std::{
+  cell::Cell,
+  collections::HashSet,
+  fs,
+  path::{Path, PathBuf},
+  rc::Rc,
+};
 
 use const_format::formatcp;
 use glow::*;
 
+use super::error::GdxError;
+
 const MVP_MATRIX: &'static str = "u_projTrans";
 const POSITION: &'static str = "a_position";
 const COLOR: &'static str = "a_color";
@@ -13,14 +21,35 @@ const NORMAL: &'static str = "a_normal";
 const TANGENT: &'static str = "a_tangent";
 const BINORMAL: &'static str = "a_binormal";
 const BONE_WEIGHT: &'static str = "a_boneWeight";
+const TEXTURE_INDEX: &'static str = "a_texIndex";
 
 pub struct ShaderProgram {
   pub gl: Rc<Context>,
-  pub vs_source: &'static str,
+  // the code that was compiled, after includes, defines and the version header, which is
+  // what the line numbers of a compile log refer to
+  pub vs_source: String,
   pub vs: Shader,
-  pub fs_source: &'static str,
+  pub fs_source: String,
   pub fs: Shader,
   pub program: Program,
+  disposed: Cell<bool>,
+}
+
+// what happens to the sources on top of the version header, see `try_from_sources`
+#[derive(Debug, Clone, Default)]
+pub struct ShaderOptions {
+  // `#define NAME VALUE` lines put right after the header, an empty value only defines NAME
+  pub defines: Vec<(String, String)>,
+  // where `#include "file"` in sources given as strings is looked up, included and loaded
+  // files include relative to their own directory
+  pub include_dir: PathBuf,
+}
+
+impl ShaderOptions {
+  pub fn define(&mut self, name: &str, value: &str) -> &mut Self {
+    self.defines.push((name.to_string(), value.to_string()));
+    self
+  }
 }
 
 pub const WHITE_VS: &str = formatcp!(
@@ -146,6 +175,61 @@ void main () {{
 "#
 );
 
+// COLORED_TEXTURED with a texture unit per vertex, GLSL ES 1.00 can only index sampler
+// arrays with constants so the fragment shader picks one of the 8 units every GLES2 device
+// has with a chain of ifs
+pub const MULTI_TEXTURED_VS: &str = formatcp!(
+  r#"#version 100
+attribute vec4 {POSITION};
+attribute vec4 {COLOR};
+attribute vec2 {TEXCOORDS};
+attribute float {TEXTURE_INDEX};
+uniform mat4 {MVP_MATRIX};
+
+varying vec4 v_color;
+varying vec2 v_texCoords;
+varying float v_texIndex;
+
+void main() {{
+  v_color = {COLOR};
+  v_color.a = v_color.a * (256.0/255.0);
+  v_texCoords = {TEXCOORDS};
+  v_texIndex = {TEXTURE_INDEX};
+  gl_Position = {MVP_MATRIX} * {POSITION};
+}}
+"#
+);
+
+pub const MULTI_TEXTURED_FS: &str = formatcp!(
+  r#"#version 100
+#ifdef GL_ES
+  #define LOWP lowp
+  precision mediump float;
+#else
+  #define LOWP
+#endif
+varying LOWP vec4 v_color;
+varying vec2 v_texCoords;
+varying float v_texIndex;
+uniform sampler2D {SAMPLER}s[8];
+
+vec4 sampleTexture() {{
+  if (v_texIndex < 0.5) return texture2D({SAMPLER}s[0], v_texCoords);
+  if (v_texIndex < 1.5) return texture2D({SAMPLER}s[1], v_texCoords);
+  if (v_texIndex < 2.5) return texture2D({SAMPLER}s[2], v_texCoords);
+  if (v_texIndex < 3.5) return texture2D({SAMPLER}s[3], v_texCoords);
+  if (v_texIndex < 4.5) return texture2D({SAMPLER}s[4], v_texCoords);
+  if (v_texIndex < 5.5) return texture2D({SAMPLER}s[5], v_texCoords);
+  if (v_texIndex < 6.5) return texture2D({SAMPLER}s[6], v_texCoords);
+  return texture2D({SAMPLER}s[7], v_texCoords);
+}}
+
+void main () {{
+  gl_FragColor = v_color * sampleTexture();
+}}
+"#
+);
+
 impl ShaderProgram {
   pub const MVP_MATRIX: &'static str = MVP_MATRIX;
   pub const POSITION: &'static str = POSITION;
@@ -157,51 +241,156 @@ impl ShaderProgram {
   pub const TANGENT: &'static str = TANGENT;
   pub const BINORMAL: &'static str = BINORMAL;
   pub const BONE_WEIGHT: &'static str = BONE_WEIGHT;
+  pub const TEXTURE_INDEX: &'static str = TEXTURE_INDEX;
 
-  pub fn compile_shader(
-    gl: &Context,
-    source: &'static str,
-    shader_type: u32,
-  ) -> Result<Shader, String> {
+  // a shader that fails to compile is deleted again, the error carries the info log
+  pub fn compile_shader(gl: &Context, source: &str, shader_type: u32) -> Result<Shader, GdxError> {
     unsafe {
-      let shader = gl.create_shader(shader_type).unwrap();
+      let shader = gl.create_shader(shader_type).map_err(GdxError::Gl)?;
       gl.shader_source(shader, source);
       gl.compile_shader(shader);
       if !gl.get_shader_compile_status(shader) {
-        let info_log = gl.get_shader_info_log(shader);
-        return Err(info_log);
+        let log = gl.get_shader_info_log(shader);
+        gl.delete_shader(shader);
+        return Err(GdxError::ShaderCompile {
+          stage: stage_name(shader_type),
+          log,
+        });
       }
       Ok(shader)
     }
   }
 
-  pub fn compile_program(gl: &Context, vs: &Shader, fs: &Shader) -> Result<Program, String> {
+  pub fn compile_program(gl: &Context, vs: &Shader, fs: &Shader) -> Result<Program, GdxError> {
     unsafe {
-      let program = gl.create_program().unwrap();
+      let program = gl.create_program().map_err(GdxError::Gl)?;
       gl.attach_shader(program, *vs);
       gl.attach_shader(program, *fs);
       gl.link_program(program);
       if !gl.get_program_link_status(program) {
-        let info_log = gl.get_program_info_log(program);
-        return Err(info_log);
+        let log = gl.get_program_info_log(program);
+        gl.delete_program(program);
+        return Err(GdxError::ProgramLink { log });
       }
       Ok(program)
     }
   }
 
-  pub fn new(gl: &Rc<Context>, vs_source: &'static str, fs_source: &'static str) -> Self {
-    let vs = Self::compile_shader(&gl, vs_source, VERTEX_SHADER).unwrap();
-    let fs = Self::compile_shader(&gl, fs_source, FRAGMENT_SHADER).unwrap();
-    let program = ShaderProgram::compile_program(&gl, &vs, &fs).unwrap();
+  // panics with the compile or link log, see `try_new`
+  pub fn new(gl: &Rc<Context>, vs_source: &str, fs_source: &str) -> Self {
+    match Self::try_new(gl, vs_source, fs_source) {
+      Ok(shader) => shader,
+      Err(error) => panic!("{}", error),
+    }
+  }
+
+  pub fn try_new(gl: &Rc<Context>, vs_source: &str, fs_source: &str) -> Result<Self, GdxError> {
+    Self::try_from_sources(
+      gl,
+      vs_source.to_string(),
+      fs_source.to_string(),
+      &ShaderOptions::default(),
+    )
+  }
+
+  // panics if an include can't be read or the shader doesn't build, see `try_from_sources`
+  pub fn from_sources(
+    gl: &Rc<Context>,
+    vs_source: String,
+    fs_source: String,
+    options: &ShaderOptions,
+  ) -> Self {
+    match Self::try_from_sources(gl, vs_source, fs_source, options) {
+      Ok(shader) => shader,
+      Err(error) => panic!("{}", error),
+    }
+  }
+
+  // sources are GLSL ES 1.00 like the constants above, a `#version 100` line is replaced by
+  // the header of the context, see `version_header`. a source with any other version is
+  // compiled for that version and only gets the defines
+  pub fn try_from_sources(
+    gl: &Rc<Context>,
+    vs_source: String,
+    fs_source: String,
+    options: &ShaderOptions,
+  ) -> Result<Self, GdxError> {
+    let vs_source = preprocess(
+      gl.version(),
+      &vs_source,
+      "vertex shader",
+      &options.include_dir,
+      HashSet::new(),
+      VERTEX_SHADER,
+      options,
+    )?;
+    let fs_source = preprocess(
+      gl.version(),
+      &fs_source,
+      "fragment shader",
+      &options.include_dir,
+      HashSet::new(),
+      FRAGMENT_SHADER,
+      options,
+    )?;
+    Self::try_compile(gl, vs_source, fs_source)
+  }
+
+  // panics if a file can't be read or the shader doesn't build, see `try_load`
+  pub fn load_from_files(
+    gl: &Rc<Context>,
+    vs_file: String,
+    fs_file: String,
+    options: &ShaderOptions,
+  ) -> Self {
+    match Self::try_load(gl, vs_file, fs_file, options) {
+      Ok(shader) => shader,
+      Err(error) => panic!("{}", error),
+    }
+  }
 
-    Self {
+  // like `try_from_sources`, includes are relative to the file they're in
+  pub fn try_load(
+    gl: &Rc<Context>,
+    vs_file: String,
+    fs_file: String,
+    options: &ShaderOptions,
+  ) -> Result<Self, GdxError> {
+    let vs_source = load(gl.version(), &vs_file, VERTEX_SHADER, options)?;
+    let fs_source = load(gl.version(), &fs_file, FRAGMENT_SHADER, options)?;
+    Self::try_compile(gl, vs_source, fs_source)
+  }
+
+  fn try_compile(gl: &Rc<Context>, vs_source: String, fs_source: String) -> Result<Self, GdxError> {
+    let vs = Self::compile_shader(&gl, &vs_source, VERTEX_SHADER)?;
+    // nothing owns the shaders yet, they have to be deleted by hand on the way out
+    let fs = match Self::compile_shader(&gl, &fs_source, FRAGMENT_SHADER) {
+      Ok(fs) => fs,
+      Err(error) => {
+        unsafe { gl.delete_shader(vs) };
+        return Err(error);
+      }
+    };
+    let program = match ShaderProgram::compile_program(&gl, &vs, &fs) {
+      Ok(program) => program,
+      Err(error) => {
+        unsafe {
+          gl.delete_shader(vs);
+          gl.delete_shader(fs);
+        }
+        return Err(error);
+      }
+    };
+
+    Ok(Self {
       gl: Rc::clone(gl),
       vs_source,
       vs,
       fs_source,
       fs,
       program,
-    }
+      disposed: Cell::new(false),
+    })
   }
 
   pub fn bind(&self) {
@@ -216,41 +405,52 @@ impl ShaderProgram {
     }
   }
 
-  pub fn set_uniform_i(&self, name: &str, value: i32) {
+  pub fn set_uniform_i(&self, name: &str, value: i32) -> Result<(), GdxError> {
     unsafe {
-      let location = self.gl.get_uniform_location(self.program, name).unwrap();
+      let location = self.uniform_location(name)?;
       self.gl.uniform_1_i32(Some(&location), value);
     }
+    Ok(())
   }
 
-  pub fn set_uniform_iv(&self, name: &str, values: &[i32]) {
+  pub fn set_uniform_iv(&self, name: &str, values: &[i32]) -> Result<(), GdxError> {
     unsafe {
-      let location = self.gl.get_uniform_location(self.program, name).unwrap();
+      let location = self.uniform_location(name)?;
       self.gl.uniform_1_i32_slice(Some(&location), values);
     }
+    Ok(())
   }
 
-  pub fn set_uniform_f(&self, name: &str, value: f32) {
+  pub fn set_uniform_f(&self, name: &str, value: f32) -> Result<(), GdxError> {
     unsafe {
-      let location = self.gl.get_uniform_location(self.program, name).unwrap();
+      let location = self.uniform_location(name)?;
       self.gl.uniform_1_f32(Some(&location), value);
     }
+    Ok(())
   }
 
-  pub fn set_uniform_2f(&self, name: &str, value1: f32, value2: f32) {
+  pub fn set_uniform_2f(&self, name: &str, value1: f32, value2: f32) -> Result<(), GdxError> {
     unsafe {
-      let location = self.gl.get_uniform_location(self.program, name).unwrap();
+      let location = self.uniform_location(name)?;
       self.gl.uniform_2_f32(Some(&location), value1, value2);
     }
+    Ok(())
   }
 
-  pub fn set_uniform_3f(&self, name: &str, value1: f32, value2: f32, value3: f32) {
+  pub fn set_uniform_3f(
+    &self,
+    name: &str,
+    value1: f32,
+    value2: f32,
+    value3: f32,
+  ) -> Result<(), GdxError> {
     unsafe {
-      let location = self.gl.get_uniform_location(self.program, name).unwrap();
+      let location = self.uniform_location(name)?;
       self
         .gl
         .uniform_3_f32(Some(&location), value1, value2, value3);
     }
+    Ok(())
   }
 
   pub fn set_uniform_f_with_location(&self, location: &UniformLocation, value: f32) {
@@ -273,40 +473,51 @@ impl ShaderProgram {
     }
   }
 
-  pub fn set_uniform_4f(&self, name: &str, value1: f32, value2: f32, value3: f32, value4: f32) {
+  pub fn set_uniform_4f(
+    &self,
+    name: &str,
+    value1: f32,
+    value2: f32,
+    value3: f32,
+    value4: f32,
+  ) -> Result<(), GdxError> {
     unsafe {
-      let location = self.gl.get_uniform_location(self.program, name).unwrap();
+      let location = self.uniform_location(name)?;
       self
         .gl
         .uniform_4_f32(Some(&location), value1, value2, value3, value4);
     }
+    Ok(())
   }
 
-  pub fn set_uniform_2x2f(&self, name: &str, values: &[f32]) {
+  pub fn set_uniform_2x2f(&self, name: &str, values: &[f32]) -> Result<(), GdxError> {
     unsafe {
-      let location = self.gl.get_uniform_location(self.program, name).unwrap();
+      let location = self.uniform_location(name)?;
       self
         .gl
         .uniform_matrix_2_f32_slice(Some(&location), false, values);
     }
+    Ok(())
   }
 
-  pub fn set_uniform_3x3f(&self, name: &str, values: &[f32]) {
+  pub fn set_uniform_3x3f(&self, name: &str, values: &[f32]) -> Result<(), GdxError> {
     unsafe {
-      let location = self.gl.get_uniform_location(self.program, name).unwrap();
+      let location = self.uniform_location(name)?;
       self
         .gl
         .uniform_matrix_3_f32_slice(Some(&location), false, values);
     }
+    Ok(())
   }
 
-  pub fn set_uniform_4x4f(&self, name: &str, values: &[f32]) {
+  pub fn set_uniform_4x4f(&self, name: &str, values: &[f32]) -> Result<(), GdxError> {
     unsafe {
-      let location = self.gl.get_uniform_location(self.program, name).unwrap();
+      let location = self.uniform_location(name)?;
       self
         .gl
         .uniform_matrix_4_f32_slice(Some(&location), false, values);
     }
+    Ok(())
   }
 
   pub fn set_uniform_4x4f_with_location(&self, location: &UniformLocation, values: &[f32]) {
@@ -317,11 +528,19 @@ impl ShaderProgram {
     }
   }
 
-  pub fn set_uniform_3fv(&self, name: &str, values: &[f32]) {
+  pub fn set_uniform_3fv(&self, name: &str, values: &[f32]) -> Result<(), GdxError> {
     unsafe {
-      let location = self.gl.get_uniform_location(self.program, name).unwrap();
+      let location = self.uniform_location(name)?;
       self.gl.uniform_3_f32_slice(Some(&location), values);
     }
+    Ok(())
+  }
+
+  fn uniform_location(&self, name: &str) -> Result<UniformLocation, GdxError> {
+    match self.get_uniform_location(name) {
+      Some(location) => Ok(location),
+      None => Err(GdxError::MissingUniform(name.to_string())),
+    }
   }
 
   // TODO: pendatic stuff
@@ -333,7 +552,11 @@ impl ShaderProgram {
     unsafe { self.gl.get_attrib_location(self.program, name) }
   }
 
+  // safe to call more than once, Drop calls it again
   pub fn dispose(&self) {
+    if self.disposed.replace(true) {
+      return;
+    }
     unsafe {
       self.gl.delete_shader(self.vs);
       self.gl.delete_shader(self.fs);
@@ -358,8 +581,338 @@ impl ShaderProgram {
   }
 }
 
+fn stage_name(shader_type: u32) -> &'static str {
+  match shader_type {
+    VERTEX_SHADER => "vertex",
+    _ => "fragment",
+  }
+}
+
+// the first line of every source written for GLSL ES 1.00. GLES 2, GLES 3 and WebGL take
+// `#version 100` as is, desktop core profiles, like the 4.1 one glutin gets on macOS, reject
+// it so desktop GL gets its own GLSL version with the ES keywords defined onto it
+pub fn version_header(gl: &Context, shader_type: u32) -> String {
+  header_for(gl.version(), shader_type)
+}
+
+// split from `version_header` so the mapping doesn't need a context
+fn header_for(version: &Version, shader_type: u32) -> String {
+  if version.is_embedded {
+    return "#version 100\n".to_string();
+  }
+  let glsl = match (version.major, version.minor) {
+    (0..=2, _) => 120,
+    (3, 0) => 130,
+    (3, 1) => 140,
+    (3, 2) => 150,
+    _ => 330,
+  };
+  let mut header = format!("#version {}\n", glsl);
+  match glsl {
+    // no precision qualifiers before GLSL 1.30
+    120 => header.push_str("#define lowp\n#define mediump\n#define highp\n"),
+    _ => match shader_type {
+      VERTEX_SHADER => header.push_str("#define attribute in\n#define varying out\n"),
+      _ => header.push_str(
+        "#define varying in\n\
+         #define texture2D texture\n\
+         #define textureCube texture\n\
+         out vec4 gdx_FragColor;\n\
+         #define gl_FragColor gdx_FragColor\n",
+      ),
+    },
+  }
+  header
+}
+
+fn read_source(path: &Path) -> Result<String, GdxError> {
+  match fs::read_to_string(path) {
+    Ok(text) => Ok(text),
+    Err(error) => Err(GdxError::Io {
+      path: path.to_string_lossy().into_owned(),
+      message: error.to_string(),
+    }),
+  }
+}
+
+fn load(
+  version: &Version,
+  file_name: &str,
+  shader_type: u32,
+  options: &ShaderOptions,
+) -> Result<String, GdxError> {
+  let path = Path::new(file_name);
+  let source = read_source(path)?;
+  let dir = match path.parent() {
+    Some(dir) => dir.to_path_buf(),
+    None => PathBuf::new(),
+  };
+  // a file can't include the file that includes it
+  let mut included = HashSet::new();
+  included.insert(fs::canonicalize(path).unwrap_or(path.to_path_buf()));
+  preprocess(
+    version,
+    &source,
+    file_name,
+    &dir,
+    included,
+    shader_type,
+    options,
+  )
+}
+
+// `name` is what errors in `source` itself are reported against, `dir` where its includes are
+// and `included` the files that are left out, `version` is the context's GL version
+fn preprocess(
+  gl_version: &Version,
+  source: &str,
+  name: &str,
+  dir: &Path,
+  mut included: HashSet<PathBuf>,
+  shader_type: u32,
+  options: &ShaderOptions,
+) -> Result<String, GdxError> {
+  let mut body = String::new();
+  resolve_includes(source, name, dir, &mut included, &mut body)?;
+
+  let mut code = String::new();
+  let version = body
+    .lines()
+    .map(str::trim)
+    .find(|line| !line.is_empty())
+    .and_then(|line| line.strip_prefix("#version"))
+    .map(str::trim);
+  let body = match version {
+    Some(_) => body
+      .trim_start()
+      .split_once('\n')
+      .map_or("", |(_, rest)| rest),
+    None => &body,
+  };
+  match version {
+    Some("100") | None => code.push_str(&header_for(gl_version, shader_type)),
+    Some(version) => code.push_str(&format!("#version {}\n", version)),
+  }
+  for (name, value) in &options.defines {
+    code.push_str(&format!("#define {} {}\n", name, value));
+  }
+  code.push_str(body);
+  Ok(code)
+}
+
+// pastes the file of every `#include "file"` line in its place, a file that was already
+// pasted is left out the next time, like `#pragma once`
+fn resolve_includes(
+  source: &str,
+  name: &str,
+  dir: &Path,
+  included: &mut HashSet<PathBuf>,
+  output: &mut String,
+) -> Result<(), GdxError> {
+  for (number, line) in source.lines().enumerate() {
+    let include = match line.trim_start().strip_prefix("#include") {
+      Some(include) => include.trim(),
+      None => {
+        output.push_str(line);
+        output.push('\n');
+        continue;
+      }
+    };
+    let file_name = match include
+      .strip_prefix('"')
+      .and_then(|include| include.strip_suffix('"'))
+    {
+      Some(file_name) => file_name,
+      None => {
+        return Err(GdxError::Parse {
+          path: name.to_string(),
+          line: number + 1,
+          message: format!("expected #include \"file\", got #include {}", include),
+        })
+      }
+    };
+    let path = dir.join(file_name);
+    if !included.insert(fs::canonicalize(&path).unwrap_or(path.clone())) {
+      continue;
+    }
+    let text = read_source(&path)?;
+    let include_dir = match path.parent() {
+      Some(dir) => dir.to_path_buf(),
+      None => PathBuf::new(),
+    };
+    resolve_includes(
+      &text,
+      &path.to_string_lossy(),
+      &include_dir,
+      included,
+      output,
+    )?;
+  }
+  Ok(())
+}
+
 impl Drop for ShaderProgram {
   fn drop(&mut self) {
     self.dispose();
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn gl_version(major: u32, minor: u32, is_embedded: bool) -> Version {
+    Version {
+      major,
+      minor,
+      is_embedded,
+      revision: None,
+      vendor_info: String::new(),
+    }
+  }
+
+  // a fresh directory of include files for each test
+  fn include_dir(test: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("my_game_shader_{}_{}", test, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    for (name, text) in files {
+      let path = dir.join(name);
+      fs::create_dir_all(path.parent().unwrap()).unwrap();
+      fs::write(path, text).unwrap();
+    }
+    dir
+  }
+
+  fn resolve(source: &str, dir: &Path) -> Result<String, GdxError> {
+    let mut output = String::new();
+    resolve_includes(source, "shader", dir, &mut HashSet::new(), &mut output)?;
+    Ok(output)
+  }
+
+  #[test]
+  fn includes_are_nested_and_relative_to_their_file() {
+    let dir = include_dir(
+      "nested",
+      &[
+        ("lib/light.glsl", "#include \"common.glsl\"\nfloat light;"),
+        ("lib/common.glsl", "float common;"),
+      ],
+    );
+    let output = resolve("#include \"lib/light.glsl\"\nvoid main() {}", &dir);
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(
+      output.unwrap(),
+      "float common;\nfloat light;\nvoid main() {}\n"
+    );
+  }
+
+  #[test]
+  fn a_file_is_only_pasted_once() {
+    let dir = include_dir(
+      "once",
+      &[
+        ("a.glsl", "#include \"common.glsl\"\nfloat a;"),
+        ("b.glsl", "#include \"common.glsl\"\nfloat b;"),
+        ("common.glsl", "float common;"),
+      ],
+    );
+    let output = resolve("#include \"a.glsl\"\n#include \"b.glsl\"", &dir);
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(output.unwrap(), "float common;\nfloat a;\nfloat b;\n");
+  }
+
+  #[test]
+  fn include_cycles_end() {
+    let dir = include_dir(
+      "cycle",
+      &[
+        ("a.glsl", "#include \"b.glsl\"\nfloat a;"),
+        ("b.glsl", "#include \"a.glsl\"\nfloat b;"),
+      ],
+    );
+    let output = resolve("#include \"a.glsl\"", &dir);
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(output.unwrap(), "float b;\nfloat a;\n");
+  }
+
+  #[test]
+  fn missing_and_malformed_includes_fail() {
+    let dir = include_dir("missing", &[]);
+    match resolve("#include \"nowhere.glsl\"", &dir) {
+      Err(GdxError::Io { path, .. }) => assert!(path.ends_with("nowhere.glsl")),
+      result => panic!("expected an io error, got {:?}", result),
+    }
+    match resolve("float a;\n#include <nowhere.glsl>", &dir) {
+      Err(GdxError::Parse { path, line, .. }) => assert_eq!((path.as_str(), line), ("shader", 2)),
+      result => panic!("expected a parse error, got {:?}", result),
+    }
+  }
+
+  fn preprocess_with(version: &Version, source: &str, options: &ShaderOptions) -> String {
+    preprocess(
+      version,
+      source,
+      "shader",
+      Path::new(""),
+      HashSet::new(),
+      FRAGMENT_SHADER,
+      options,
+    )
+    .unwrap()
+  }
+
+  #[test]
+  fn defines_go_after_the_version() {
+    let mut options = ShaderOptions::default();
+    options.define("LIGHTS", "4").define("FOG", "");
+    let gles = gl_version(3, 0, true);
+    assert_eq!(
+      preprocess_with(&gles, "\n#version 100\nvoid main() {}", &options),
+      "#version 100\n#define LIGHTS 4\n#define FOG \nvoid main() {}\n"
+    );
+    // without a version line the header goes first all the same
+    assert_eq!(
+      preprocess_with(&gles, "void main() {}", &options),
+      "#version 100\n#define LIGHTS 4\n#define FOG \nvoid main() {}\n"
+    );
+    // any other version is kept as written
+    assert_eq!(
+      preprocess_with(
+        &gl_version(4, 1, false),
+        "#version 300 es\nvoid main() {}",
+        &options
+      ),
+      "#version 300 es\n#define LIGHTS 4\n#define FOG \nvoid main() {}\n"
+    );
+  }
+
+  #[test]
+  fn es_sources_get_a_desktop_header() {
+    assert_eq!(
+      header_for(&gl_version(2, 0, true), VERTEX_SHADER),
+      "#version 100\n"
+    );
+    assert_eq!(
+      header_for(&gl_version(2, 1, false), FRAGMENT_SHADER),
+      "#version 120\n#define lowp\n#define mediump\n#define highp\n"
+    );
+    for (major, minor, glsl) in [
+      (3, 0, 130),
+      (3, 1, 140),
+      (3, 2, 150),
+      (3, 3, 330),
+      (4, 1, 330),
+    ] {
+      let version = gl_version(major, minor, false);
+      let vertex = header_for(&version, VERTEX_SHADER);
+      assert!(vertex.starts_with(&format!("#version {}\n", glsl)));
+      assert!(vertex.contains("#define attribute in\n"));
+      assert!(vertex.contains("#define varying out\n"));
+      let fragment = header_for(&version, FRAGMENT_SHADER);
+      assert!(fragment.starts_with(&format!("#version {}\n", glsl)));
+      assert!(fragment.contains("#define varying in\n"));
+      assert!(fragment.contains("#define texture2D texture\n"));
+      assert!(fragment.contains("#define gl_FragColor gdx_FragColor\n"));
+    }
+  }
+}
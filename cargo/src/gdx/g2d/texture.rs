@@ -1,11 +1,228 @@
-use std::rc::Rc;
+use std::{cell::Cell, rc::Rc};
 
 use glow::*;
+use image::{DynamicImage, ImageError};
 
+use super::error::GdxError;
+
+// how pixels are laid out in `ImageData` and on the GPU, RGB and LUMINANCE take 3 and 1
+// bytes per pixel instead of 4 for images that don't need them
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PixelFormat {
+  Rgba,
+  Rgb,
+  // the image's brightness only, shaders read it into r, g and b with alpha 1
+  Luminance,
+}
+
+impl PixelFormat {
+  pub fn gl_format(&self) -> u32 {
+    match self {
+      PixelFormat::Rgba => RGBA,
+      PixelFormat::Rgb => RGB,
+      PixelFormat::Luminance => LUMINANCE,
+    }
+  }
+  // desktop core profiles dropped LUMINANCE, from GL 3.3 it's uploaded as RED and swizzled
+  // back to r, g and b when sampled, GLES and WebGL keep it
+  fn is_red_on(&self, gl: &Context) -> bool {
+    let version = gl.version();
+    *self == PixelFormat::Luminance
+      && !version.is_embedded
+      && (version.major, version.minor) >= (3, 3)
+  }
+  pub fn bytes_per_pixel(&self) -> usize {
+    match self {
+      PixelFormat::Rgba => 4,
+      PixelFormat::Rgb => 3,
+      PixelFormat::Luminance => 1,
+    }
+  }
+}
+
+// width * height pixels checked against the length of the data, GL reads that many bytes
+// whatever the slice holds
 pub struct ImageData<'a> {
-  pub(crate) width: u32,
-  pub(crate) height: u32,
-  pub(crate) data: &'a [u8],
+  width: u32,
+  height: u32,
+  format: PixelFormat,
+  data: &'a [u8],
+}
+
+impl<'a> ImageData<'a> {
+  // panics if `data` is too short, see `try_new`
+  pub fn new(width: u32, height: u32, format: PixelFormat, data: &'a [u8]) -> Self {
+    match Self::try_new(width, height, format, data) {
+      Ok(image) => image,
+      Err(error) => panic!("{}", error),
+    }
+  }
+  // `data` holds `width * height` pixels in `format`, rows top to bottom, extra bytes are
+  // ignored
+  pub fn try_new(
+    width: u32,
+    height: u32,
+    format: PixelFormat,
+    data: &'a [u8],
+  ) -> Result<Self, GdxError> {
+    let needed = (width as usize)
+      .checked_mul(height as usize)
+      .and_then(|pixels| pixels.checked_mul(format.bytes_per_pixel()));
+    match needed {
+      Some(needed) if needed <= data.len() => Ok(Self {
+        width,
+        height,
+        format,
+        data: &data[..needed],
+      }),
+      _ => Err(GdxError::ImageDataSize {
+        width,
+        height,
+        length: data.len(),
+      }),
+    }
+  }
+  pub fn width(&self) -> u32 {
+    self.width
+  }
+  pub fn height(&self) -> u32 {
+    self.height
+  }
+  pub fn format(&self) -> PixelFormat {
+    self.format
+  }
+  pub fn data(&self) -> &'a [u8] {
+    self.data
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextureFilter {
+  Nearest,
+  Linear,
+  // the mipmap variants, named like libGDX: the level's filter, then how levels are picked
+  MipMapNearestNearest,
+  MipMapLinearNearest,
+  MipMapNearestLinear,
+  MipMapLinearLinear,
+}
+
+impl TextureFilter {
+  pub fn gl_enum(&self) -> u32 {
+    match self {
+      TextureFilter::Nearest => NEAREST,
+      TextureFilter::Linear => LINEAR,
+      TextureFilter::MipMapNearestNearest => NEAREST_MIPMAP_NEAREST,
+      TextureFilter::MipMapLinearNearest => LINEAR_MIPMAP_NEAREST,
+      TextureFilter::MipMapNearestLinear => NEAREST_MIPMAP_LINEAR,
+      TextureFilter::MipMapLinearLinear => LINEAR_MIPMAP_LINEAR,
+    }
+  }
+  pub fn from_gl_enum(value: u32) -> Option<Self> {
+    match value {
+      NEAREST => Some(TextureFilter::Nearest),
+      LINEAR => Some(TextureFilter::Linear),
+      NEAREST_MIPMAP_NEAREST => Some(TextureFilter::MipMapNearestNearest),
+      LINEAR_MIPMAP_NEAREST => Some(TextureFilter::MipMapLinearNearest),
+      NEAREST_MIPMAP_LINEAR => Some(TextureFilter::MipMapNearestLinear),
+      LINEAR_MIPMAP_LINEAR => Some(TextureFilter::MipMapLinearLinear),
+      _ => None,
+    }
+  }
+  pub fn is_mipmap(&self) -> bool {
+    match self {
+      TextureFilter::Nearest | TextureFilter::Linear => false,
+      _ => true,
+    }
+  }
+  // the filter within a level, what a mipmap filter becomes on a texture without mipmaps
+  pub fn without_mipmaps(&self) -> Self {
+    match self {
+      TextureFilter::Nearest
+      | TextureFilter::MipMapNearestNearest
+      | TextureFilter::MipMapNearestLinear => TextureFilter::Nearest,
+      _ => TextureFilter::Linear,
+    }
+  }
+}
+
+// GLES2 only repeats power of two textures, others are clamped, see `Texture::set_wrap`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextureWrap {
+  ClampToEdge,
+  Repeat,
+  MirroredRepeat,
+}
+
+impl TextureWrap {
+  pub fn gl_enum(&self) -> u32 {
+    match self {
+      TextureWrap::ClampToEdge => CLAMP_TO_EDGE,
+      TextureWrap::Repeat => REPEAT,
+      TextureWrap::MirroredRepeat => MIRRORED_REPEAT,
+    }
+  }
+  pub fn from_gl_enum(value: u32) -> Option<Self> {
+    match value {
+      CLAMP_TO_EDGE => Some(TextureWrap::ClampToEdge),
+      REPEAT => Some(TextureWrap::Repeat),
+      MIRRORED_REPEAT => Some(TextureWrap::MirroredRepeat),
+      _ => None,
+    }
+  }
+}
+
+// sampler state of a texture, mipmaps are generated when asked for or when the min filter
+// needs them, GLES2 can only do that for power of two sizes, see `Texture::generate_mipmaps`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TextureOptions {
+  pub min_filter: TextureFilter,
+  pub mag_filter: TextureFilter,
+  pub wrap_s: TextureWrap,
+  pub wrap_t: TextureWrap,
+  pub mipmaps: bool,
+}
+
+impl Default for TextureOptions {
+  fn default() -> Self {
+    Self {
+      min_filter: TextureFilter::Linear,
+      mag_filter: TextureFilter::Linear,
+      wrap_s: TextureWrap::ClampToEdge,
+      wrap_t: TextureWrap::ClampToEdge,
+      mipmaps: false,
+    }
+  }
+}
+
+impl TextureOptions {
+  // pixel art, no smoothing when scaled
+  pub fn nearest() -> Self {
+    Self {
+      min_filter: TextureFilter::Nearest,
+      mag_filter: TextureFilter::Nearest,
+      ..Default::default()
+    }
+  }
+}
+
+// how a decoded image is turned into pixels before the upload
+#[derive(Debug, Clone, Copy)]
+pub struct LoadOptions {
+  // multiplies r, g and b by alpha, for blending with ONE, ONE_MINUS_SRC_ALPHA
+  pub premultiply_alpha: bool,
+  pub format: PixelFormat,
+  pub texture: TextureOptions,
+}
+
+impl Default for LoadOptions {
+  fn default() -> Self {
+    Self {
+      premultiply_alpha: false,
+      format: PixelFormat::Rgba,
+      texture: TextureOptions::default(),
+    }
+  }
 }
 
 #[derive(Debug)]
@@ -14,57 +231,226 @@ pub struct Texture {
   pub texture: NativeTexture,
   pub width: u32,
   pub height: u32,
+  pub format: PixelFormat,
+  options: Cell<TextureOptions>,
+  disposed: Cell<bool>,
+}
+
+// any color type and bit depth `image` decodes ends up as 8 bit `options.format`
+fn convert(img: DynamicImage, options: &LoadOptions) -> (u32, u32, Vec<u8>) {
+  let mut rgba = img.into_rgba8();
+  if options.premultiply_alpha {
+    for pixel in rgba.pixels_mut() {
+      let alpha = pixel[3] as u32;
+      for channel in 0..3 {
+        pixel[channel] = ((pixel[channel] as u32 * alpha + 127) / 255) as u8;
+      }
+    }
+  }
+  let (width, height) = rgba.dimensions();
+  let data = match options.format {
+    PixelFormat::Rgba => rgba.into_raw(),
+    PixelFormat::Rgb => DynamicImage::ImageRgba8(rgba).into_rgb8().into_raw(),
+    PixelFormat::Luminance => DynamicImage::ImageRgba8(rgba).into_luma8().into_raw(),
+  };
+  (width, height, data)
+}
+
+pub(super) fn image_error(path: String, error: ImageError) -> GdxError {
+  match error {
+    ImageError::Unsupported(error) => GdxError::UnsupportedFormat {
+      path,
+      message: error.to_string(),
+    },
+    error => GdxError::Image {
+      path,
+      message: error.to_string(),
+    },
+  }
 }
 
 impl Texture {
-  // TODO: cover all options, make an option struct too
   pub fn new_white_texture(gl: &Rc<Context>) -> Rc<Self> {
-    let data = ImageData {
-      width: 1,
-      height: 1,
-      data: &[255, 255, 255, 255],
-    };
+    let data = ImageData::new(1, 1, PixelFormat::Rgba, &[255, 255, 255, 255]);
     Self::new(gl, data)
   }
+  // panics if the file can't be loaded, see `try_load`
   pub fn load_from_file(gl: &Rc<Context>, file_name: String) -> Rc<Self> {
-    let img = image::open(file_name).unwrap();
-    let data = img.as_rgba8().unwrap();
-    let data = ImageData {
-      width: data.width(),
-      height: data.height(),
-      data: data.as_raw(),
+    match Self::try_load(gl, file_name) {
+      Ok(texture) => texture,
+      Err(error) => panic!("{}", error),
+    }
+  }
+  pub fn try_load(gl: &Rc<Context>, file_name: String) -> Result<Rc<Self>, GdxError> {
+    Self::try_load_with_options(gl, file_name, &LoadOptions::default())
+  }
+  pub fn try_load_with_options(
+    gl: &Rc<Context>,
+    file_name: String,
+    options: &LoadOptions,
+  ) -> Result<Rc<Self>, GdxError> {
+    let img = match image::open(&file_name) {
+      Ok(img) => img,
+      Err(error) => return Err(image_error(file_name, error)),
     };
-    Self::new(gl, data)
+    let (width, height, data) = convert(img, options);
+    let data = ImageData::new(width, height, options.format, &data);
+    Self::try_new_with_options(gl, data, &options.texture)
+  }
+  // an encoded image (png, jpeg, ...) already in memory, e.g. from `include_bytes!`,
+  // the format is guessed from its first bytes
+  pub fn from_bytes(gl: &Rc<Context>, bytes: &[u8], options: &LoadOptions) -> Rc<Self> {
+    match Self::try_from_bytes(gl, bytes, options) {
+      Ok(texture) => texture,
+      Err(error) => panic!("{}", error),
+    }
+  }
+  pub fn try_from_bytes(
+    gl: &Rc<Context>,
+    bytes: &[u8],
+    options: &LoadOptions,
+  ) -> Result<Rc<Self>, GdxError> {
+    let img = match image::load_from_memory(bytes) {
+      Ok(img) => img,
+      Err(error) => return Err(image_error("<memory>".to_string(), error)),
+    };
+    let (width, height, data) = convert(img, options);
+    let data = ImageData::new(width, height, options.format, &data);
+    Self::try_new_with_options(gl, data, &options.texture)
   }
   pub fn new(gl: &Rc<Context>, data: ImageData) -> Rc<Self> {
+    Self::new_with_options(gl, data, &TextureOptions::default())
+  }
+  pub fn new_with_options(gl: &Rc<Context>, data: ImageData, options: &TextureOptions) -> Rc<Self> {
+    match Self::try_new_with_options(gl, data, options) {
+      Ok(texture) => texture,
+      Err(error) => panic!("{}", error),
+    }
+  }
+  pub fn try_new(gl: &Rc<Context>, data: ImageData) -> Result<Rc<Self>, GdxError> {
+    Self::try_new_with_options(gl, data, &TextureOptions::default())
+  }
+  pub fn try_new_with_options(
+    gl: &Rc<Context>,
+    data: ImageData,
+    options: &TextureOptions,
+  ) -> Result<Rc<Self>, GdxError> {
     unsafe {
-      let texture = gl.create_texture().unwrap();
+      let texture = gl.create_texture().map_err(GdxError::Gl)?;
       gl.bind_texture(TEXTURE_2D, Some(texture));
-      gl.tex_parameter_i32(TEXTURE_2D, TEXTURE_MIN_FILTER, LINEAR as i32);
-      gl.tex_parameter_i32(TEXTURE_2D, TEXTURE_MAG_FILTER, LINEAR as i32);
-      gl.tex_parameter_i32(TEXTURE_2D, TEXTURE_WRAP_S, CLAMP_TO_EDGE as i32);
-      gl.tex_parameter_i32(TEXTURE_2D, TEXTURE_WRAP_T, CLAMP_TO_EDGE as i32);
+      let red = data.format.is_red_on(gl);
+      let (internal_format, format) = match red {
+        true => (R8, RED),
+        false => (data.format.gl_format(), data.format.gl_format()),
+      };
+      // rows of RGB and LUMINANCE pixels aren't 4 byte aligned
+      gl.pixel_store_i32(UNPACK_ALIGNMENT, 1);
       gl.tex_image_2d(
         TEXTURE_2D,
         0,
-        RGBA as i32,
+        internal_format as i32,
         data.width as i32,
         data.height as i32,
         0,
-        RGBA,
+        format,
         UNSIGNED_BYTE,
-        Some(&data.data),
+        Some(data.data),
       );
-      gl.generate_mipmap(TEXTURE_2D);
-      Rc::new(Self {
+      gl.pixel_store_i32(UNPACK_ALIGNMENT, 4);
+      if red {
+        let swizzle = [RED as i32, RED as i32, RED as i32, ONE as i32];
+        gl.tex_parameter_i32_slice(TEXTURE_2D, TEXTURE_SWIZZLE_RGBA, &swizzle);
+      }
+      let texture = Rc::new(Self {
         gl: Rc::clone(gl),
         texture,
         width: data.width,
         height: data.height,
-      })
+        format: data.format,
+        options: Cell::new(TextureOptions {
+          mipmaps: false,
+          ..*options
+        }),
+        disposed: Cell::new(false),
+      });
+      if options.mipmaps {
+        texture.generate_mipmaps();
+      }
+      texture.set_filter(options.min_filter, options.mag_filter);
+      texture.set_wrap(options.wrap_s, options.wrap_t);
+      Ok(texture)
     }
   }
 
+  pub fn get_options(&self) -> TextureOptions {
+    self.options.get()
+  }
+
+  // GLES2 and WebGL1 sample a non power of two texture as black when it has mipmaps, a
+  // mipmap filter or repeats
+  fn limited_npot(&self) -> bool {
+    let version = self.gl.version();
+    version.is_embedded
+      && version.major < 3
+      && !(self.width.is_power_of_two() && self.height.is_power_of_two())
+  }
+
+  // builds the mipmap chain from the current level 0, again after it changed, does nothing
+  // where a texture of this size can't have mipmaps
+  pub fn generate_mipmaps(&self) {
+    if self.limited_npot() {
+      return;
+    }
+    self.bind();
+    unsafe {
+      self.gl.generate_mipmap(TEXTURE_2D);
+    }
+    let mut options = self.options.get();
+    options.mipmaps = true;
+    self.options.set(options);
+  }
+
+  // a mipmap min filter generates the mipmaps if there are none yet, where a texture of this
+  // size can't have mipmaps it falls back to the filter within a level
+  pub fn set_filter(&self, min_filter: TextureFilter, mag_filter: TextureFilter) {
+    let (min_filter, mag_filter) = match self.limited_npot() {
+      true => (min_filter.without_mipmaps(), mag_filter.without_mipmaps()),
+      false => (min_filter, mag_filter),
+    };
+    let mut options = self.options.get();
+    if min_filter.is_mipmap() && !options.mipmaps {
+      self.generate_mipmaps();
+      options.mipmaps = true;
+    }
+    self.bind();
+    unsafe {
+      let gl = &self.gl;
+      gl.tex_parameter_i32(TEXTURE_2D, TEXTURE_MIN_FILTER, min_filter.gl_enum() as i32);
+      gl.tex_parameter_i32(TEXTURE_2D, TEXTURE_MAG_FILTER, mag_filter.gl_enum() as i32);
+    }
+    options.min_filter = min_filter;
+    options.mag_filter = mag_filter;
+    self.options.set(options);
+  }
+
+  // clamps where a texture of this size can't repeat
+  pub fn set_wrap(&self, wrap_s: TextureWrap, wrap_t: TextureWrap) {
+    let (wrap_s, wrap_t) = match self.limited_npot() {
+      true => (TextureWrap::ClampToEdge, TextureWrap::ClampToEdge),
+      false => (wrap_s, wrap_t),
+    };
+    self.bind();
+    unsafe {
+      let gl = &self.gl;
+      gl.tex_parameter_i32(TEXTURE_2D, TEXTURE_WRAP_S, wrap_s.gl_enum() as i32);
+      gl.tex_parameter_i32(TEXTURE_2D, TEXTURE_WRAP_T, wrap_t.gl_enum() as i32);
+    }
+    let mut options = self.options.get();
+    options.wrap_s = wrap_s;
+    options.wrap_t = wrap_t;
+    self.options.set(options);
+  }
+
   pub fn bind(&self) {
     self.bind_to(0);
   }
@@ -82,7 +468,11 @@ impl Texture {
     }
   }
 
+  // safe to call more than once, Drop calls it again
   pub fn dispose(&self) {
+    if self.disposed.replace(true) {
+      return;
+    }
     unsafe {
       self.gl.delete_texture(self.texture);
     }
@@ -100,3 +490,24 @@ impl PartialEq for Texture {
     self.texture == other.texture
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn image_data_needs_every_pixel() {
+    let bytes = [0; 7];
+    assert_eq!(
+      ImageData::try_new(1, 2, PixelFormat::Rgb, &bytes[..5]).err(),
+      Some(GdxError::ImageDataSize {
+        width: 1,
+        height: 2,
+        length: 5,
+      })
+    );
+    let image = ImageData::try_new(1, 2, PixelFormat::Rgb, &bytes).unwrap();
+    assert_eq!(image.data().len(), 6);
+    assert!(ImageData::try_new(u32::MAX, u32::MAX, PixelFormat::Rgba, &bytes).is_err());
+  }
+}
@@ -0,0 +1,691 @@
+use std::{
+  fs,
+  path::{Path, PathBuf},
+  rc::Rc,
+};
+
+use super::{
+  error::GdxError,
+  texture::{LoadOptions, PixelFormat, Texture, TextureFilter, TextureOptions, TextureWrap},
+  texture_region::TextureRegion,
+};
+
+// a page of a libGDX `.atlas` file as written, before its texture is loaded
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtlasPageData {
+  // relative to the atlas file
+  pub texture_file: String,
+  pub width: u32,
+  pub height: u32,
+  pub format: PixelFormat,
+  pub options: TextureOptions,
+  // the image already has premultiplied alpha, blend with ONE, ONE_MINUS_SRC_ALPHA
+  pub pma: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AtlasRegionData {
+  pub page: usize,
+  pub name: String,
+  // top left of the packed rectangle in the page, y down
+  pub left: u32,
+  pub top: u32,
+  // the packed size, upright
+  pub width: u32,
+  pub height: u32,
+  // whitespace stripped by the packer, offset_y counts from the bottom like in libGDX
+  pub offset_x: f32,
+  pub offset_y: f32,
+  pub original_width: u32,
+  pub original_height: u32,
+  pub degrees: i32,
+  // -1 for regions that aren't part of a numbered sequence
+  pub index: i32,
+  // ninepatch edges, left, right, top, bottom
+  pub split: Option<[i32; 4]>,
+  pub pad: Option<[i32; 4]>,
+}
+
+// the parsed text of a libGDX/TexturePacker atlas, both the indented format written before
+// libGDX 1.9.14 and the newer one with `bounds` and `offsets`
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextureAtlasData {
+  pub pages: Vec<AtlasPageData>,
+  pub regions: Vec<AtlasRegionData>,
+}
+
+// "key: a, b, c" to the key and up to 4 values, None for a line without a colon
+fn read_entry(line: &str) -> Option<(&str, Vec<&str>)> {
+  let line = line.trim();
+  let colon = line.find(':')?;
+  let values = line[colon + 1..]
+    .split(',')
+    .map(|value| value.trim())
+    .take(4)
+    .collect();
+  Some((line[..colon].trim(), values))
+}
+
+fn parse_filter(value: &str) -> Option<TextureFilter> {
+  match value {
+    "Nearest" => Some(TextureFilter::Nearest),
+    "Linear" => Some(TextureFilter::Linear),
+    "MipMap" | "MipMapLinearLinear" => Some(TextureFilter::MipMapLinearLinear),
+    "MipMapNearestNearest" => Some(TextureFilter::MipMapNearestNearest),
+    "MipMapLinearNearest" => Some(TextureFilter::MipMapLinearNearest),
+    "MipMapNearestLinear" => Some(TextureFilter::MipMapNearestLinear),
+    _ => None,
+  }
+}
+
+fn filter_name(filter: TextureFilter) -> &'static str {
+  match filter {
+    TextureFilter::Nearest => "Nearest",
+    TextureFilter::Linear => "Linear",
+    TextureFilter::MipMapNearestNearest => "MipMapNearestNearest",
+    TextureFilter::MipMapLinearNearest => "MipMapLinearNearest",
+    TextureFilter::MipMapNearestLinear => "MipMapNearestLinear",
+    TextureFilter::MipMapLinearLinear => "MipMapLinearLinear",
+  }
+}
+
+impl TextureAtlasData {
+  // `path` only shows up in errors
+  pub fn parse(path: &str, text: &str) -> Result<Self, GdxError> {
+    let mut atlas = Self {
+      pages: vec![],
+      regions: vec![],
+    };
+    let mut lines = text.lines().enumerate().peekable();
+    let error = |line: usize, message: String| GdxError::Parse {
+      path: path.to_string(),
+      line: line + 1,
+      message,
+    };
+    // entries have to be numbers
+    let numbers = |line: usize, key: &str, values: &[&str], count: usize| {
+      if values.len() < count {
+        return Err(error(line, format!("{} needs {} values", key, count)));
+      }
+      values[..count]
+        .iter()
+        .map(|value| {
+          value
+            .parse::<i32>()
+            .map_err(|_| error(line, format!("{} is not a number in {}", value, key)))
+        })
+        .collect::<Result<Vec<i32>, GdxError>>()
+    };
+    let unsigned = |line: usize, key: &str, values: &[&str], count: usize| {
+      let numbers = numbers(line, key, values, count)?;
+      match numbers.iter().all(|number| *number >= 0) {
+        true => Ok(
+          numbers
+            .iter()
+            .map(|number| *number as u32)
+            .collect::<Vec<u32>>(),
+        ),
+        false => Err(error(line, format!("{} can't be negative", key))),
+      }
+    };
+
+    // header entries before the first page are ignored, like libGDX does
+    while let Some((_, line)) = lines.peek() {
+      match line.trim().is_empty() || read_entry(line).is_some() {
+        true => lines.next(),
+        false => break,
+      };
+    }
+
+    // a blank line ends a page, the next line names the next page's image
+    let mut in_page = false;
+    while let Some((_, line)) = lines.next() {
+      if line.trim().is_empty() {
+        in_page = false;
+        continue;
+      }
+      if !in_page {
+        let mut page = AtlasPageData {
+          texture_file: line.trim().to_string(),
+          width: 0,
+          height: 0,
+          format: PixelFormat::Rgba,
+          options: TextureOptions::default(),
+          pma: false,
+        };
+        while let Some((number, line)) = lines.peek() {
+          let number = *number;
+          let (key, values) = match read_entry(line) {
+            Some(entry) => entry,
+            None => break,
+          };
+          match key {
+            "size" => {
+              let size = unsigned(number, key, &values, 2)?;
+              page.width = size[0];
+              page.height = size[1];
+            }
+            // textures are uploaded with 8 bits per channel, pages without alpha as RGB
+            "format" => {
+              page.format = match values[0] {
+                "RGB888" | "RGB565" => PixelFormat::Rgb,
+                _ => PixelFormat::Rgba,
+              }
+            }
+            "filter" => {
+              let filter = |value: &str| match parse_filter(value) {
+                Some(filter) => Ok(filter),
+                None => Err(error(number, format!("unknown filter {}", value))),
+              };
+              page.options.min_filter = filter(values[0])?;
+              page.options.mag_filter = filter(values.get(1).unwrap_or(&values[0]))?;
+            }
+            "repeat" => {
+              let repeat = |axis: char| match values[0].contains(axis) {
+                true => TextureWrap::Repeat,
+                false => TextureWrap::ClampToEdge,
+              };
+              page.options.wrap_s = repeat('x');
+              page.options.wrap_t = repeat('y');
+            }
+            "pma" => page.pma = values[0] == "true",
+            _ => (),
+          }
+          lines.next();
+        }
+        atlas.pages.push(page);
+        in_page = true;
+        continue;
+      }
+
+      let mut region = AtlasRegionData {
+        page: atlas.pages.len() - 1,
+        name: line.trim().to_string(),
+        left: 0,
+        top: 0,
+        width: 0,
+        height: 0,
+        offset_x: 0.,
+        offset_y: 0.,
+        original_width: 0,
+        original_height: 0,
+        degrees: 0,
+        index: -1,
+        split: None,
+        pad: None,
+      };
+      while let Some((number, line)) = lines.peek() {
+        let number = *number;
+        let (key, values) = match read_entry(line) {
+          Some(entry) => entry,
+          None => break,
+        };
+        match key {
+          "xy" => {
+            let xy = unsigned(number, key, &values, 2)?;
+            region.left = xy[0];
+            region.top = xy[1];
+          }
+          "size" => {
+            let size = unsigned(number, key, &values, 2)?;
+            region.width = size[0];
+            region.height = size[1];
+          }
+          "bounds" => {
+            let bounds = unsigned(number, key, &values, 4)?;
+            region.left = bounds[0];
+            region.top = bounds[1];
+            region.width = bounds[2];
+            region.height = bounds[3];
+          }
+          "offset" => {
+            let offset = numbers(number, key, &values, 2)?;
+            region.offset_x = offset[0] as f32;
+            region.offset_y = offset[1] as f32;
+          }
+          "orig" => {
+            let original = unsigned(number, key, &values, 2)?;
+            region.original_width = original[0];
+            region.original_height = original[1];
+          }
+          "offsets" => {
+            let offsets = numbers(number, key, &values, 4)?;
+            region.offset_x = offsets[0] as f32;
+            region.offset_y = offsets[1] as f32;
+            let original = unsigned(number, key, &values[2..], 2)?;
+            region.original_width = original[0];
+            region.original_height = original[1];
+          }
+          "rotate" => {
+            region.degrees = match values[0] {
+              "true" => 90,
+              "false" => 0,
+              _ => numbers(number, key, &values, 1)?[0],
+            }
+          }
+          "index" => region.index = numbers(number, key, &values, 1)?[0],
+          "split" => {
+            let split = numbers(number, key, &values, 4)?;
+            region.split = Some([split[0], split[1], split[2], split[3]]);
+          }
+          "pad" => {
+            let pad = numbers(number, key, &values, 4)?;
+            region.pad = Some([pad[0], pad[1], pad[2], pad[3]]);
+          }
+          _ => (),
+        }
+        lines.next();
+      }
+      if region.original_width == 0 && region.original_height == 0 {
+        region.original_width = region.width;
+        region.original_height = region.height;
+      }
+      atlas.regions.push(region);
+    }
+    Ok(atlas)
+  }
+
+  // the newer libGDX format, entries that hold their default are left out
+  pub fn to_text(&self) -> String {
+    let mut text = String::new();
+    for (page_index, page) in self.pages.iter().enumerate() {
+      if page_index > 0 {
+        text.push('\n');
+      }
+      text.push_str(&format!("{}\n", page.texture_file));
+      text.push_str(&format!("size: {}, {}\n", page.width, page.height));
+      let format = match page.format {
+        PixelFormat::Rgb => "RGB888",
+        _ => "RGBA8888",
+      };
+      text.push_str(&format!("format: {}\n", format));
+      text.push_str(&format!(
+        "filter: {}, {}\n",
+        filter_name(page.options.min_filter),
+        filter_name(page.options.mag_filter)
+      ));
+      let repeat = match (page.options.wrap_s, page.options.wrap_t) {
+        (TextureWrap::Repeat, TextureWrap::Repeat) => "xy",
+        (TextureWrap::Repeat, _) => "x",
+        (_, TextureWrap::Repeat) => "y",
+        _ => "none",
+      };
+      text.push_str(&format!("repeat: {}\n", repeat));
+      if page.pma {
+        text.push_str("pma: true\n");
+      }
+      for region in self
+        .regions
+        .iter()
+        .filter(|region| region.page == page_index)
+      {
+        text.push_str(&format!("{}\n", region.name));
+        text.push_str(&format!(
+          "bounds: {}, {}, {}, {}\n",
+          region.left, region.top, region.width, region.height
+        ));
+        let stripped = region.offset_x != 0.
+          || region.offset_y != 0.
+          || region.original_width != region.width
+          || region.original_height != region.height;
+        if stripped {
+          text.push_str(&format!(
+            "offsets: {}, {}, {}, {}\n",
+            region.offset_x, region.offset_y, region.original_width, region.original_height
+          ));
+        }
+        match region.degrees {
+          0 => (),
+          90 => text.push_str("rotate: true\n"),
+          degrees => text.push_str(&format!("rotate: {}\n", degrees)),
+        }
+        if region.index != -1 {
+          text.push_str(&format!("index: {}\n", region.index));
+        }
+        if let Some([left, right, top, bottom]) = region.split {
+          text.push_str(&format!(
+            "split: {}, {}, {}, {}\n",
+            left, right, top, bottom
+          ));
+        }
+        if let Some([left, right, top, bottom]) = region.pad {
+          text.push_str(&format!("pad: {}, {}, {}, {}\n", left, right, top, bottom));
+        }
+      }
+    }
+    text
+  }
+}
+
+pub struct AtlasPage {
+  pub data: AtlasPageData,
+  pub texture: Rc<Texture>,
+}
+
+#[derive(Debug, Clone)]
+pub struct AtlasRegion {
+  // the packed pixels, rotated back upright when drawn
+  pub region: TextureRegion,
+  pub name: String,
+  pub index: i32,
+  pub offset_x: f32,
+  pub offset_y: f32,
+  pub packed_width: u32,
+  pub packed_height: u32,
+  pub original_width: u32,
+  pub original_height: u32,
+  pub degrees: i32,
+  pub split: Option<[i32; 4]>,
+  pub pad: Option<[i32; 4]>,
+}
+
+pub struct TextureAtlas {
+  pub pages: Vec<AtlasPage>,
+  pub regions: Vec<AtlasRegion>,
+}
+
+impl TextureAtlas {
+  // panics if the atlas or one of its pages can't be loaded, see `try_load`
+  pub fn load_from_file(gl: &Rc<glow::Context>, file_name: String) -> Self {
+    match Self::try_load(gl, file_name) {
+      Ok(atlas) => atlas,
+      Err(error) => panic!("{}", error),
+    }
+  }
+
+  // the page images are next to the atlas file
+  pub fn try_load(gl: &Rc<glow::Context>, file_name: String) -> Result<Self, GdxError> {
+    let text = match fs::read_to_string(&file_name) {
+      Ok(text) => text,
+      Err(error) => {
+        return Err(GdxError::Io {
+          path: file_name,
+          message: error.to_string(),
+        })
+      }
+    };
+    let data = TextureAtlasData::parse(&file_name, &text)?;
+    let images_dir = match Path::new(&file_name).parent() {
+      Some(dir) => dir.to_path_buf(),
+      None => PathBuf::new(),
+    };
+    Self::try_from_data(gl, data, &images_dir)
+  }
+
+  pub fn try_from_data(
+    gl: &Rc<glow::Context>,
+    data: TextureAtlasData,
+    images_dir: &Path,
+  ) -> Result<Self, GdxError> {
+    let mut textures = vec![];
+    for page in &data.pages {
+      let path = images_dir.join(&page.texture_file);
+      let options = LoadOptions {
+        premultiply_alpha: false,
+        format: page.format,
+        texture: page.options,
+      };
+      textures.push(Texture::try_load_with_options(
+        gl,
+        path.to_string_lossy().into_owned(),
+        &options,
+      )?);
+    }
+    Ok(Self::from_textures(data, textures))
+  }
+
+  // pages already uploaded, one texture per page in order, e.g. packed at runtime
+  pub fn from_textures(data: TextureAtlasData, textures: Vec<Rc<Texture>>) -> Self {
+    let pages: Vec<AtlasPage> = data
+      .pages
+      .into_iter()
+      .zip(textures)
+      .map(|(page, texture)| AtlasPage {
+        data: page,
+        texture,
+      })
+      .collect();
+    let regions = data
+      .regions
+      .into_iter()
+      .map(|region| {
+        let rotated = region.degrees == 90;
+        // a rotated region is stored with its width and height swapped
+        let (width, height) = match rotated {
+          true => (region.height, region.width),
+          false => (region.width, region.height),
+        };
+        let texture = &pages[region.page].texture;
+        let mut texture_region =
+          TextureRegion::from_rect(texture, region.left, region.top, width, height);
+        texture_region.rotated = rotated;
+        AtlasRegion {
+          region: texture_region,
+          name: region.name,
+          index: region.index,
+          offset_x: region.offset_x,
+          offset_y: region.offset_y,
+          packed_width: region.width,
+          packed_height: region.height,
+          original_width: region.original_width,
+          original_height: region.original_height,
+          degrees: region.degrees,
+          split: region.split,
+          pad: region.pad,
+        }
+      })
+      .collect();
+    Self { pages, regions }
+  }
+
+  // the first region with this name
+  pub fn find_region(&self, name: &str) -> Option<&AtlasRegion> {
+    self.regions.iter().find(|region| region.name == name)
+  }
+
+  pub fn find_region_with_index(&self, name: &str, index: i32) -> Option<&AtlasRegion> {
+    self
+      .regions
+      .iter()
+      .find(|region| region.name == name && region.index == index)
+  }
+
+  // every region with this name in file order, e.g. the frames of an animation
+  pub fn find_regions(&self, name: &str) -> Vec<&AtlasRegion> {
+    self
+      .regions
+      .iter()
+      .filter(|region| region.name == name)
+      .collect()
+  }
+
+  pub fn dispose(&self) {
+    for page in &self.pages {
+      page.texture.dispose();
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // written by libGDX's packer before 1.9.14, regions indented under their page
+  const LEGACY: &str = "
+hero.png
+size: 64, 32
+format: RGBA8888
+filter: Nearest, Linear
+repeat: none
+walk
+  rotate: false
+  xy: 2, 4
+  size: 10, 12
+  orig: 16, 16
+  offset: 3, 1
+  index: 2
+button
+  rotate: true
+  xy: 20, 0
+  size: 8, 6
+  split: 1, 2, 3, 4
+  pad: 5, 6, 7, 8
+  orig: 8, 6
+  offset: 0, 0
+  index: -1
+
+tiles.png
+size: 16, 16
+format: RGB888
+filter: MipMapLinearLinear, Linear
+repeat: xy
+grass
+  rotate: false
+  xy: 0, 0
+  size: 16, 16
+  orig: 16, 16
+  offset: 0, 0
+  index: -1
+";
+
+  const CURRENT: &str = "hero.png
+size: 64, 32
+filter: Nearest, Linear
+pma: true
+walk
+bounds: 2, 4, 10, 12
+offsets: 3, 1, 16, 16
+index: 2
+button
+bounds: 20, 0, 8, 6
+rotate: 90
+split: 1, 2, 3, 4
+pad: 5, 6, 7, 8
+
+tiles.png
+size: 16, 16
+format: RGB888
+repeat: x
+grass
+bounds: 0, 0, 16, 16
+rotate: 270
+";
+
+  fn parse(text: &str) -> TextureAtlasData {
+    TextureAtlasData::parse("test.atlas", text).unwrap()
+  }
+
+  fn parse_error(text: &str) -> (usize, String) {
+    match TextureAtlasData::parse("test.atlas", text) {
+      Err(GdxError::Parse {
+        path,
+        line,
+        message,
+      }) => {
+        assert_eq!(path, "test.atlas");
+        (line, message)
+      }
+      result => panic!("expected a parse error, got {:?}", result),
+    }
+  }
+
+  fn assert_regions(atlas: &TextureAtlasData) {
+    assert_eq!(atlas.regions.len(), 3);
+    let walk = &atlas.regions[0];
+    assert_eq!((walk.page, walk.name.as_str()), (0, "walk"));
+    assert_eq!(
+      (walk.left, walk.top, walk.width, walk.height),
+      (2, 4, 10, 12)
+    );
+    assert_eq!((walk.offset_x, walk.offset_y), (3., 1.));
+    assert_eq!((walk.original_width, walk.original_height), (16, 16));
+    assert_eq!((walk.degrees, walk.index), (0, 2));
+    assert_eq!((walk.split, walk.pad), (None, None));
+
+    let button = &atlas.regions[1];
+    assert_eq!((button.page, button.name.as_str()), (0, "button"));
+    assert_eq!(
+      (button.left, button.top, button.width, button.height),
+      (20, 0, 8, 6)
+    );
+    // no orig or offsets, the region wasn't stripped
+    assert_eq!((button.original_width, button.original_height), (8, 6));
+    assert_eq!((button.degrees, button.index), (90, -1));
+    assert_eq!(button.split, Some([1, 2, 3, 4]));
+    assert_eq!(button.pad, Some([5, 6, 7, 8]));
+
+    let grass = &atlas.regions[2];
+    assert_eq!((grass.page, grass.name.as_str()), (1, "grass"));
+    assert_eq!((grass.width, grass.height), (16, 16));
+  }
+
+  #[test]
+  fn parses_the_legacy_format() {
+    let atlas = parse(LEGACY);
+    assert_eq!(atlas.pages.len(), 2);
+    let hero = &atlas.pages[0];
+    assert_eq!(hero.texture_file, "hero.png");
+    assert_eq!((hero.width, hero.height), (64, 32));
+    assert_eq!(hero.format, PixelFormat::Rgba);
+    assert_eq!(hero.options.min_filter, TextureFilter::Nearest);
+    assert_eq!(hero.options.mag_filter, TextureFilter::Linear);
+    assert_eq!(hero.options.wrap_s, TextureWrap::ClampToEdge);
+    assert!(!hero.pma);
+    let tiles = &atlas.pages[1];
+    assert_eq!(tiles.texture_file, "tiles.png");
+    assert_eq!(tiles.format, PixelFormat::Rgb);
+    assert_eq!(tiles.options.min_filter, TextureFilter::MipMapLinearLinear);
+    assert_eq!(tiles.options.wrap_s, TextureWrap::Repeat);
+    assert_eq!(tiles.options.wrap_t, TextureWrap::Repeat);
+    assert_regions(&atlas);
+    assert_eq!(atlas.regions[2].degrees, 0);
+  }
+
+  #[test]
+  fn parses_the_current_format() {
+    let atlas = parse(CURRENT);
+    assert_eq!(atlas.pages.len(), 2);
+    assert!(atlas.pages[0].pma);
+    assert_eq!(atlas.pages[0].options.mag_filter, TextureFilter::Linear);
+    assert_eq!(atlas.pages[1].options.wrap_s, TextureWrap::Repeat);
+    assert_eq!(atlas.pages[1].options.wrap_t, TextureWrap::ClampToEdge);
+    assert_regions(&atlas);
+    assert_eq!(atlas.regions[2].degrees, 270);
+  }
+
+  #[test]
+  fn both_formats_agree() {
+    let (legacy, current) = (parse(LEGACY), parse(CURRENT));
+    for (legacy, current) in legacy.regions.iter().zip(&current.regions).take(2) {
+      assert_eq!(legacy, current);
+    }
+  }
+
+  #[test]
+  fn errors_point_at_the_line() {
+    assert_eq!(
+      parse_error("\npage.png\nsize: 8, x\n"),
+      (3, "x is not a number in size".to_string())
+    );
+    assert_eq!(
+      parse_error("page.png\nsize: 8, 8\nregion\nbounds: 0, 0, 4\n"),
+      (4, "bounds needs 4 values".to_string())
+    );
+    assert_eq!(
+      parse_error("page.png\nsize: 8, 8\nregion\n  xy: -1, 0\n"),
+      (4, "xy can't be negative".to_string())
+    );
+    assert_eq!(
+      parse_error("page.png\nfilter: Blurry\n"),
+      (2, "unknown filter Blurry".to_string())
+    );
+  }
+
+  #[test]
+  fn to_text_parses_back() {
+    for text in [LEGACY, CURRENT] {
+      let atlas = parse(text);
+      assert_eq!(parse(&atlas.to_text()), atlas);
+    }
+  }
+}
@@ -0,0 +1,722 @@
+use std::{fs, path::Path, rc::Rc};
+
+use image::ColorType;
+
+use super::{
+  error::GdxError,
+  texture::{image_error, ImageData, PixelFormat, Texture, TextureOptions},
+  texture_atlas::{AtlasPageData, AtlasRegionData, TextureAtlas, TextureAtlasData},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PackStrategy {
+  // tries every free rectangle left on the page, packs tightest
+  MaxRects,
+  // only tracks the outline of what's placed so far, faster but wastes more on mixed sizes
+  Skyline,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PackerSettings {
+  pub max_width: u32,
+  pub max_height: u32,
+  // transparent pixels between images
+  pub padding: u32,
+  // edge pixels repeated around each image so linear filtering doesn't pull in the neighbours
+  pub bleed: u32,
+  // page sizes round up, GLES2 needs it for mipmaps and repeat, the max size rounds down
+  pub power_of_two: bool,
+  pub strategy: PackStrategy,
+  pub premultiply_alpha: bool,
+  pub options: TextureOptions,
+}
+
+impl Default for PackerSettings {
+  fn default() -> Self {
+    Self {
+      max_width: 2048,
+      max_height: 2048,
+      padding: 2,
+      bleed: 0,
+      power_of_two: false,
+      strategy: PackStrategy::MaxRects,
+      premultiply_alpha: false,
+      options: TextureOptions::default(),
+    }
+  }
+}
+
+struct PackerImage {
+  name: String,
+  index: i32,
+  width: u32,
+  height: u32,
+  // RGBA, rows top to bottom
+  pixels: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Rect {
+  x: u32,
+  y: u32,
+  width: u32,
+  height: u32,
+}
+
+impl Rect {
+  fn intersects(&self, other: &Rect) -> bool {
+    self.x < other.x + other.width
+      && other.x < self.x + self.width
+      && self.y < other.y + other.height
+      && other.y < self.y + self.height
+  }
+
+  fn contains(&self, other: &Rect) -> bool {
+    other.x >= self.x
+      && other.y >= self.y
+      && other.x + other.width <= self.x + self.width
+      && other.y + other.height <= self.y + self.height
+  }
+}
+
+// a skyline segment, everything below `y` is taken
+struct SkylineNode {
+  x: u32,
+  y: u32,
+  width: u32,
+}
+
+// the free space of one page, y down
+enum Bin {
+  // every maximal free rectangle, they overlap
+  MaxRects(Vec<Rect>),
+  Skyline {
+    width: u32,
+    height: u32,
+    nodes: Vec<SkylineNode>,
+  },
+}
+
+impl Bin {
+  fn new(strategy: PackStrategy, width: u32, height: u32) -> Self {
+    match strategy {
+      PackStrategy::MaxRects => Bin::MaxRects(vec![Rect {
+        x: 0,
+        y: 0,
+        width,
+        height,
+      }]),
+      PackStrategy::Skyline => Bin::Skyline {
+        width,
+        height,
+        nodes: vec![SkylineNode { x: 0, y: 0, width }],
+      },
+    }
+  }
+
+  // the top left corner the rectangle went to, None if the page is too full
+  fn insert(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+    match self {
+      Bin::MaxRects(free) => Self::insert_max_rects(free, width, height),
+      Bin::Skyline {
+        width: bin_width,
+        height: bin_height,
+        nodes,
+      } => Self::insert_skyline(nodes, *bin_width, *bin_height, width, height),
+    }
+  }
+
+  // best short side fit, the free rectangle that leaves the least on its shorter side
+  fn insert_max_rects(free: &mut Vec<Rect>, width: u32, height: u32) -> Option<(u32, u32)> {
+    let best = free
+      .iter()
+      .filter(|rect| rect.width >= width && rect.height >= height)
+      .min_by_key(|rect| {
+        let left_x = rect.width - width;
+        let left_y = rect.height - height;
+        (left_x.min(left_y), left_x.max(left_y))
+      })?;
+    let placed = Rect {
+      x: best.x,
+      y: best.y,
+      width,
+      height,
+    };
+    // every free rectangle the new one overlaps is cut into the up to 4 parts around it
+    let mut split = vec![];
+    for rect in free.iter() {
+      if !rect.intersects(&placed) {
+        split.push(*rect);
+        continue;
+      }
+      if placed.x > rect.x {
+        split.push(Rect {
+          width: placed.x - rect.x,
+          ..*rect
+        });
+      }
+      if placed.x + placed.width < rect.x + rect.width {
+        split.push(Rect {
+          x: placed.x + placed.width,
+          width: rect.x + rect.width - placed.x - placed.width,
+          ..*rect
+        });
+      }
+      if placed.y > rect.y {
+        split.push(Rect {
+          height: placed.y - rect.y,
+          ..*rect
+        });
+      }
+      if placed.y + placed.height < rect.y + rect.height {
+        split.push(Rect {
+          y: placed.y + placed.height,
+          height: rect.y + rect.height - placed.y - placed.height,
+          ..*rect
+        });
+      }
+    }
+    // rectangles inside another one are redundant, of two equal ones the first is kept
+    let mut pruned: Vec<Rect> = vec![];
+    for (i, rect) in split.iter().enumerate() {
+      let redundant = split
+        .iter()
+        .enumerate()
+        .any(|(j, other)| i != j && other.contains(rect) && (j < i || !rect.contains(other)));
+      if !redundant {
+        pruned.push(*rect);
+      }
+    }
+    *free = pruned;
+    Some((placed.x, placed.y))
+  }
+
+  // bottom left, the position that keeps the skyline lowest, then the narrowest segment
+  fn insert_skyline(
+    nodes: &mut Vec<SkylineNode>,
+    bin_width: u32,
+    bin_height: u32,
+    width: u32,
+    height: u32,
+  ) -> Option<(u32, u32)> {
+    let fits = |index: usize| {
+      let x = nodes[index].x;
+      if x + width > bin_width {
+        return None;
+      }
+      let mut y = 0;
+      let mut remaining = width;
+      let mut node = index;
+      while remaining > 0 {
+        y = y.max(nodes[node].y);
+        if y + height > bin_height {
+          return None;
+        }
+        remaining -= remaining.min(nodes[node].width);
+        node += 1;
+      }
+      Some(y)
+    };
+    let (index, y) = (0..nodes.len())
+      .filter_map(|index| fits(index).map(|y| (index, y)))
+      .min_by_key(|(index, y)| (y + height, nodes[*index].width))?;
+    let x = nodes[index].x;
+    nodes.insert(
+      index,
+      SkylineNode {
+        x,
+        y: y + height,
+        width,
+      },
+    );
+    // the segments now under the new one shrink or go
+    let end = x + width;
+    let next = index + 1;
+    while next < nodes.len() && nodes[next].x < end {
+      let covered = end - nodes[next].x;
+      if nodes[next].width <= covered {
+        nodes.remove(next);
+      } else {
+        nodes[next].x += covered;
+        nodes[next].width -= covered;
+        break;
+      }
+    }
+    // neighbours at the same height become one segment
+    let mut i = 0;
+    while i + 1 < nodes.len() {
+      if nodes[i].y == nodes[i + 1].y {
+        nodes[i].width += nodes[i + 1].width;
+        nodes.remove(i + 1);
+      } else {
+        i += 1;
+      }
+    }
+    Some((x, y))
+  }
+}
+
+// "walk_3" is frame 3 of "walk" like in libGDX, any other name has no index
+fn split_index(name: &str) -> (String, i32) {
+  match name.rsplit_once('_') {
+    Some((prefix, suffix)) if !suffix.is_empty() && suffix.chars().all(|c| c.is_ascii_digit()) => {
+      match suffix.parse() {
+        Ok(index) => (prefix.to_string(), index),
+        Err(_) => (name.to_string(), -1),
+      }
+    }
+    _ => (name.to_string(), -1),
+  }
+}
+
+fn floor_power_of_two(value: u32) -> u32 {
+  match value {
+    0 => 0,
+    _ => 1 << (31 - value.leading_zeros()),
+  }
+}
+
+fn to_rgba(data: &ImageData) -> Vec<u8> {
+  match data.format() {
+    PixelFormat::Rgba => data.data().to_vec(),
+    PixelFormat::Rgb => data
+      .data()
+      .chunks_exact(3)
+      .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 255])
+      .collect(),
+    PixelFormat::Luminance => data
+      .data()
+      .iter()
+      .flat_map(|value| [*value, *value, *value, 255])
+      .collect(),
+  }
+}
+
+// `x` and `y` are where the image goes with its bleed, the edges are repeated outwards
+fn copy_image(page: &mut [u8], page_width: u32, image: &PackerImage, x: u32, y: u32, bleed: u32) {
+  let (width, height) = (image.width as usize, image.height as usize);
+  if width == 0 || height == 0 {
+    return;
+  }
+  let (x, y, bleed) = (x as usize, y as usize, bleed as usize);
+  let stride = page_width as usize * 4;
+  for row in 0..height {
+    let source = &image.pixels[row * width * 4..(row + 1) * width * 4];
+    let start = (y + bleed + row) * stride + x * 4;
+    for i in 0..bleed {
+      page[start + i * 4..start + i * 4 + 4].copy_from_slice(&source[..4]);
+      let right = start + (bleed + width + i) * 4;
+      page[right..right + 4].copy_from_slice(&source[source.len() - 4..]);
+    }
+    page[start + bleed * 4..start + (bleed + width) * 4].copy_from_slice(source);
+  }
+  // then the first and last row with their corners
+  let row_length = (width + bleed * 2) * 4;
+  let first = (y + bleed) * stride + x * 4;
+  let last = (y + bleed + height - 1) * stride + x * 4;
+  for i in 0..bleed {
+    page.copy_within(first..first + row_length, (y + i) * stride + x * 4);
+    page.copy_within(
+      last..last + row_length,
+      (y + bleed + height + i) * stride + x * 4,
+    );
+  }
+}
+
+// RGBA, rows top to bottom
+pub struct PackedPage {
+  pub width: u32,
+  pub height: u32,
+  pub pixels: Vec<u8>,
+}
+
+// the pages as pixels plus the atlas describing them, uploaded right away or written
+// out as a `.atlas` file and its PNGs
+pub struct PackedAtlas {
+  pub name: String,
+  pub data: TextureAtlasData,
+  pub pages: Vec<PackedPage>,
+}
+
+impl PackedAtlas {
+  // panics if a page texture can't be created, see `try_upload`
+  pub fn upload(&self, gl: &Rc<glow::Context>) -> TextureAtlas {
+    match self.try_upload(gl) {
+      Ok(atlas) => atlas,
+      Err(error) => panic!("{}", error),
+    }
+  }
+
+  pub fn try_upload(&self, gl: &Rc<glow::Context>) -> Result<TextureAtlas, GdxError> {
+    let mut textures = vec![];
+    for (page, data) in self.pages.iter().zip(&self.data.pages) {
+      let image = ImageData::new(page.width, page.height, PixelFormat::Rgba, &page.pixels);
+      textures.push(Texture::try_new_with_options(gl, image, &data.options)?);
+    }
+    Ok(TextureAtlas::from_textures(self.data.clone(), textures))
+  }
+
+  // `<name>.atlas` and a PNG per page into `dir`, which has to exist
+  pub fn write(&self, dir: &Path) -> Result<(), GdxError> {
+    for (page, data) in self.pages.iter().zip(&self.data.pages) {
+      let path = dir.join(&data.texture_file);
+      match image::save_buffer(
+        &path,
+        &page.pixels,
+        page.width,
+        page.height,
+        ColorType::Rgba8,
+      ) {
+        Ok(()) => (),
+        Err(error) => return Err(image_error(path.to_string_lossy().into_owned(), error)),
+      }
+    }
+    let path = dir.join(format!("{}.atlas", self.name));
+    match fs::write(&path, self.data.to_text()) {
+      Ok(()) => Ok(()),
+      Err(error) => Err(GdxError::Io {
+        path: path.to_string_lossy().into_owned(),
+        message: error.to_string(),
+      }),
+    }
+  }
+}
+
+// collects loose images and packs them into as few pages as fit, so a batch can draw
+// them all without switching textures
+pub struct TexturePacker {
+  pub settings: PackerSettings,
+  images: Vec<PackerImage>,
+}
+
+impl TexturePacker {
+  pub fn new(settings: PackerSettings) -> Self {
+    Self {
+      settings,
+      images: vec![],
+    }
+  }
+
+  // a trailing "_<number>" in `name` becomes the region's index
+  pub fn add_image(&mut self, name: &str, data: ImageData) {
+    let mut pixels = to_rgba(&data);
+    if self.settings.premultiply_alpha {
+      for pixel in pixels.chunks_exact_mut(4) {
+        let alpha = pixel[3] as u32;
+        for channel in pixel.iter_mut().take(3) {
+          *channel = ((*channel as u32 * alpha + 127) / 255) as u8;
+        }
+      }
+    }
+    let (name, index) = split_index(name);
+    self.images.push(PackerImage {
+      name,
+      index,
+      width: data.width(),
+      height: data.height(),
+      pixels,
+    });
+  }
+
+  // named after the file without its extension
+  pub fn try_add_file(&mut self, file_name: String) -> Result<(), GdxError> {
+    let img = match image::open(&file_name) {
+      Ok(img) => img.into_rgba8(),
+      Err(error) => return Err(image_error(file_name, error)),
+    };
+    let name = match Path::new(&file_name).file_stem() {
+      Some(stem) => stem.to_string_lossy().into_owned(),
+      None => file_name.clone(),
+    };
+    let (width, height) = img.dimensions();
+    self.add_image(
+      &name,
+      ImageData::new(width, height, PixelFormat::Rgba, img.as_raw()),
+    );
+    Ok(())
+  }
+
+  // pages are named `<name>.png`, `<name>2.png`, ... like libGDX's packer does,
+  // fails if an image doesn't fit on a page by itself
+  pub fn pack(&self, name: &str) -> Result<PackedAtlas, GdxError> {
+    let settings = &self.settings;
+    let border = settings.bleed * 2 + settings.padding;
+    // a power of two page can't grow past the largest power of two that fits the limit
+    let (max_width, max_height) = match settings.power_of_two {
+      true => (
+        floor_power_of_two(settings.max_width),
+        floor_power_of_two(settings.max_height),
+      ),
+      false => (settings.max_width, settings.max_height),
+    };
+    // padding is only needed between images, the page gets room for one more at its edges
+    let bin_width = max_width + settings.padding;
+    let bin_height = max_height + settings.padding;
+    for image in &self.images {
+      if image.width + border > bin_width || image.height + border > bin_height {
+        return Err(GdxError::ImageTooLarge {
+          name: image.name.clone(),
+          width: image.width + settings.bleed * 2,
+          height: image.height + settings.bleed * 2,
+          max_width,
+          max_height,
+        });
+      }
+    }
+
+    // big ones first, they're the hardest to fit
+    let mut remaining: Vec<usize> = (0..self.images.len()).collect();
+    remaining.sort_by_key(|index| {
+      let image = &self.images[*index];
+      (
+        std::cmp::Reverse(image.width.max(image.height)),
+        std::cmp::Reverse(image.width * image.height),
+      )
+    });
+
+    let mut atlas = PackedAtlas {
+      name: name.to_string(),
+      data: TextureAtlasData {
+        pages: vec![],
+        regions: vec![],
+      },
+      pages: vec![],
+    };
+    while !remaining.is_empty() {
+      let mut bin = Bin::new(settings.strategy, bin_width, bin_height);
+      let mut placed = vec![];
+      let mut left = vec![];
+      for index in remaining {
+        let image = &self.images[index];
+        match bin.insert(image.width + border, image.height + border) {
+          Some((x, y)) => placed.push((index, x, y)),
+          None => left.push(index),
+        }
+      }
+      remaining = left;
+      // regions are listed in the order the images were added
+      placed.sort_by_key(|(index, _, _)| *index);
+
+      let mut width = 0;
+      let mut height = 0;
+      for (index, x, y) in &placed {
+        let image = &self.images[*index];
+        width = width.max(x + image.width + settings.bleed * 2);
+        height = height.max(y + image.height + settings.bleed * 2);
+      }
+      if settings.power_of_two {
+        width = width.next_power_of_two();
+        height = height.next_power_of_two();
+      }
+      let mut pixels = vec![0; width as usize * height as usize * 4];
+      let page = atlas.pages.len();
+      for (index, x, y) in placed {
+        let image = &self.images[index];
+        copy_image(&mut pixels, width, image, x, y, settings.bleed);
+        atlas.data.regions.push(AtlasRegionData {
+          page,
+          name: image.name.clone(),
+          left: x + settings.bleed,
+          top: y + settings.bleed,
+          width: image.width,
+          height: image.height,
+          offset_x: 0.,
+          offset_y: 0.,
+          original_width: image.width,
+          original_height: image.height,
+          degrees: 0,
+          index: image.index,
+          split: None,
+          pad: None,
+        });
+      }
+      let texture_file = match page {
+        0 => format!("{}.png", name),
+        _ => format!("{}{}.png", name, page + 1),
+      };
+      atlas.data.pages.push(AtlasPageData {
+        texture_file,
+        width,
+        height,
+        format: PixelFormat::Rgba,
+        options: settings.options,
+        pma: settings.premultiply_alpha,
+      });
+      atlas.pages.push(PackedPage {
+        width,
+        height,
+        pixels,
+      });
+    }
+    Ok(atlas)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn rect((x, y): (u32, u32), width: u32, height: u32) -> Rect {
+    Rect {
+      x,
+      y,
+      width,
+      height,
+    }
+  }
+
+  // places the sizes in order, every one has to fit, inside the page and apart from the rest
+  fn assert_packs(strategy: PackStrategy, sizes: &[(u32, u32)]) -> Bin {
+    let mut bin = Bin::new(strategy, 64, 64);
+    let mut placed: Vec<Rect> = vec![];
+    for (width, height) in sizes {
+      let position = bin.insert(*width, *height).expect("fits");
+      let rect = rect(position, *width, *height);
+      assert!(rect.x + rect.width <= 64 && rect.y + rect.height <= 64);
+      for other in &placed {
+        assert!(!rect.intersects(other), "{:?} overlaps {:?}", rect, other);
+      }
+      placed.push(rect);
+    }
+    bin
+  }
+
+  #[test]
+  fn max_rects_fills_a_page_without_overlap() {
+    let mut bin = assert_packs(
+      PackStrategy::MaxRects,
+      &[(32, 32), (32, 16), (16, 16), (16, 16), (32, 32), (32, 32)],
+    );
+    // the sizes add up to the whole page
+    assert_eq!(bin.insert(1, 1), None);
+  }
+
+  #[test]
+  fn skyline_packs_without_overlap() {
+    let mut bin = assert_packs(
+      PackStrategy::Skyline,
+      &[(40, 10), (24, 30), (20, 20), (20, 20), (30, 24), (64, 10)],
+    );
+    assert_eq!(bin.insert(64, 64), None);
+  }
+
+  #[test]
+  fn bins_reject_what_is_left_over() {
+    for strategy in [PackStrategy::MaxRects, PackStrategy::Skyline] {
+      let mut bin = assert_packs(strategy, &[(64, 48)]);
+      assert_eq!(bin.insert(64, 17), None);
+      assert_eq!(bin.insert(64, 16), Some((0, 48)));
+      assert_eq!(bin.insert(1, 1), None);
+    }
+  }
+
+  #[test]
+  fn copy_image_repeats_the_edges_into_the_bleed() {
+    // 2x2, red green on top, blue white below
+    let image = PackerImage {
+      name: "image".to_string(),
+      index: -1,
+      width: 2,
+      height: 2,
+      pixels: vec![
+        255, 0, 0, 255, 0, 255, 0, 255, //
+        0, 0, 255, 255, 255, 255, 255, 255,
+      ],
+    };
+    let mut page = vec![0; 5 * 5 * 4];
+    copy_image(&mut page, 5, &image, 1, 1, 1);
+    let pixel = |x: usize, y: usize| &page[(y * 5 + x) * 4..(y * 5 + x) * 4 + 4];
+    let (red, green, blue, white) = (
+      [255, 0, 0, 255],
+      [0, 255, 0, 255],
+      [0, 0, 255, 255],
+      [255, 255, 255, 255],
+    );
+    let expected = [
+      [red, red, green, green],
+      [red, red, green, green],
+      [blue, blue, white, white],
+      [blue, blue, white, white],
+    ];
+    for (y, row) in expected.iter().enumerate() {
+      for (x, color) in row.iter().enumerate() {
+        assert_eq!(pixel(x + 1, y + 1), color, "pixel {}, {}", x + 1, y + 1);
+      }
+    }
+    // the column and row before the image position stay untouched
+    for i in 0..5 {
+      assert_eq!(pixel(0, i), [0, 0, 0, 0]);
+      assert_eq!(pixel(i, 0), [0, 0, 0, 0]);
+    }
+  }
+
+  fn add_square(packer: &mut TexturePacker, name: &str, size: u32) {
+    let pixels = vec![255; size as usize * size as usize * 4];
+    packer.add_image(name, ImageData::new(size, size, PixelFormat::Rgba, &pixels));
+  }
+
+  #[test]
+  fn pack_overflows_onto_more_pages() {
+    let mut packer = TexturePacker::new(PackerSettings {
+      max_width: 32,
+      max_height: 32,
+      padding: 0,
+      ..PackerSettings::default()
+    });
+    for i in 0..5 {
+      add_square(&mut packer, &format!("tile_{}", i), 16);
+    }
+    let atlas = packer.pack("tiles").unwrap();
+    assert_eq!(atlas.pages.len(), 2);
+    assert_eq!(atlas.data.pages[0].texture_file, "tiles.png");
+    assert_eq!(atlas.data.pages[1].texture_file, "tiles2.png");
+    let pages: Vec<usize> = atlas
+      .data
+      .regions
+      .iter()
+      .map(|region| region.page)
+      .collect();
+    assert_eq!(pages, [0, 0, 0, 0, 1]);
+    let indices: Vec<i32> = atlas
+      .data
+      .regions
+      .iter()
+      .map(|region| region.index)
+      .collect();
+    assert_eq!(indices, [0, 1, 2, 3, 4]);
+    assert_eq!((atlas.pages[1].width, atlas.pages[1].height), (16, 16));
+  }
+
+  #[test]
+  fn power_of_two_pages_stay_within_the_max_size() {
+    let mut packer = TexturePacker::new(PackerSettings {
+      max_width: 100,
+      max_height: 100,
+      padding: 0,
+      power_of_two: true,
+      ..PackerSettings::default()
+    });
+    add_square(&mut packer, "a", 50);
+    add_square(&mut packer, "b", 50);
+    let atlas = packer.pack("pot").unwrap();
+    assert_eq!(atlas.pages.len(), 2);
+    for page in &atlas.pages {
+      assert_eq!((page.width, page.height), (64, 64));
+    }
+
+    add_square(&mut packer, "c", 70);
+    match packer.pack("pot") {
+      Err(GdxError::ImageTooLarge { max_width, .. }) => assert_eq!(max_width, 64),
+      _ => panic!("a 70 pixel image doesn't fit a 64 pixel page"),
+    }
+  }
+
+  #[test]
+  fn split_index_takes_a_trailing_number() {
+    assert_eq!(split_index("walk_3"), ("walk".to_string(), 3));
+    assert_eq!(split_index("big_walk_12"), ("big_walk".to_string(), 12));
+    assert_eq!(split_index("walk"), ("walk".to_string(), -1));
+    assert_eq!(split_index("walk_"), ("walk_".to_string(), -1));
+    assert_eq!(split_index("walk_left"), ("walk_left".to_string(), -1));
+  }
+}
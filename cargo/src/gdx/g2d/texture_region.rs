@@ -0,0 +1,128 @@
+use std::rc::Rc;
+
+use super::texture::Texture;
+
+// a rectangle of a texture in pixels, y down from the top of the image like in image
+// editors and atlas files, flipping swaps u/u2 or v/v2 like libGDX
+#[derive(Debug, Clone)]
+pub struct TextureRegion {
+  pub texture: Rc<Texture>,
+  pub u: f32,
+  pub v: f32,
+  pub u2: f32,
+  pub v2: f32,
+  // the rectangle as stored in the texture
+  pub region_x: u32,
+  pub region_y: u32,
+  pub region_width: u32,
+  pub region_height: u32,
+  // stored 90 degrees counter clockwise to pack tighter, drawn upright again
+  pub rotated: bool,
+}
+
+impl TextureRegion {
+  // the whole texture
+  pub fn new(texture: &Rc<Texture>) -> Self {
+    Self::from_rect(texture, 0, 0, texture.width, texture.height)
+  }
+
+  pub fn from_rect(texture: &Rc<Texture>, x: u32, y: u32, width: u32, height: u32) -> Self {
+    let mut region = Self {
+      texture: Rc::clone(texture),
+      u: 0.,
+      v: 0.,
+      u2: 1.,
+      v2: 1.,
+      region_x: 0,
+      region_y: 0,
+      region_width: 0,
+      region_height: 0,
+      rotated: false,
+    };
+    region.set_region(x, y, width, height);
+    region
+  }
+
+  // resets flipping
+  pub fn set_region(&mut self, x: u32, y: u32, width: u32, height: u32) {
+    let texture_width = self.texture.width as f32;
+    let texture_height = self.texture.height as f32;
+    self.u = x as f32 / texture_width;
+    self.v = y as f32 / texture_height;
+    self.u2 = (x + width) as f32 / texture_width;
+    self.v2 = (y + height) as f32 / texture_height;
+    self.region_x = x;
+    self.region_y = y;
+    self.region_width = width;
+    self.region_height = height;
+  }
+
+  pub fn flip(&mut self, x: bool, y: bool) {
+    if x {
+      std::mem::swap(&mut self.u, &mut self.u2);
+    }
+    if y {
+      std::mem::swap(&mut self.v, &mut self.v2);
+    }
+  }
+
+  pub fn is_flip_x(&self) -> bool {
+    self.u > self.u2
+  }
+
+  pub fn is_flip_y(&self) -> bool {
+    self.v > self.v2
+  }
+
+  // the size it is drawn at by default, the stored one turned back for rotated regions
+  pub fn get_width(&self) -> u32 {
+    if self.rotated {
+      self.region_height
+    } else {
+      self.region_width
+    }
+  }
+
+  pub fn get_height(&self) -> u32 {
+    if self.rotated {
+      self.region_width
+    } else {
+      self.region_height
+    }
+  }
+
+  // cuts the region into tile_width x tile_height frames, row by row from the top left,
+  // a partial row or column at the right or bottom edge is left out
+  pub fn split(&self, tile_width: u32, tile_height: u32) -> Vec<Vec<TextureRegion>> {
+    let mut rows = vec![];
+    if tile_width == 0 || tile_height == 0 {
+      return rows;
+    }
+    for row in 0..self.region_height / tile_height {
+      let mut frames = vec![];
+      for column in 0..self.region_width / tile_width {
+        frames.push(Self::from_rect(
+          &self.texture,
+          self.region_x + column * tile_width,
+          self.region_y + row * tile_height,
+          tile_width,
+          tile_height,
+        ));
+      }
+      rows.push(frames);
+    }
+    rows
+  }
+
+  // a sprite sheet with `columns` x `rows` equally sized frames, in reading order
+  pub fn split_frames(texture: &Rc<Texture>, columns: u32, rows: u32) -> Vec<TextureRegion> {
+    if columns == 0 || rows == 0 {
+      return vec![];
+    }
+    Self::new(texture)
+      .split(texture.width / columns, texture.height / rows)
+      .into_iter()
+      .flatten()
+      .collect()
+  }
+}
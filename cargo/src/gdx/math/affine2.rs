@@ -0,0 +1,440 @@
+use super::{mat3::*, mat4::*, vector2::Vec2};
+
+// the top two rows of a 3x3 matrix whose last row is always [0, 0, 1], enough for
+// 2D translation, rotation, scale and shear at a fraction of the cost of Mat3
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Affine2 {
+  pub m00: f32,
+  pub m01: f32,
+  pub m02: f32,
+  pub m10: f32,
+  pub m11: f32,
+  pub m12: f32,
+}
+
+impl Affine2 {
+  pub fn new() -> Self {
+    Self {
+      m00: 1.0,
+      m01: 0.0,
+      m02: 0.0,
+      m10: 0.0,
+      m11: 1.0,
+      m12: 0.0,
+    }
+  }
+
+  pub fn idt(&mut self) -> &mut Self {
+    self.m00 = 1.0;
+    self.m01 = 0.0;
+    self.m02 = 0.0;
+    self.m10 = 0.0;
+    self.m11 = 1.0;
+    self.m12 = 0.0;
+
+    self
+  }
+
+  pub fn is_idt(&self) -> bool {
+    self.m00 == 1.0
+      && self.m01 == 0.0
+      && self.m02 == 0.0
+      && self.m10 == 0.0
+      && self.m11 == 1.0
+      && self.m12 == 0.0
+  }
+
+  pub fn set(&mut self, other: &Self) -> &mut Self {
+    *self = *other;
+
+    self
+  }
+
+  // the last row of the matrix is assumed to be [0, 0, 1] and is dropped
+  pub fn set_by_mat3(&mut self, mat: &Mat3) -> &mut Self {
+    self.m00 = mat.values[MAT3_M00];
+    self.m01 = mat.values[MAT3_M01];
+    self.m02 = mat.values[MAT3_M02];
+    self.m10 = mat.values[MAT3_M10];
+    self.m11 = mat.values[MAT3_M11];
+    self.m12 = mat.values[MAT3_M12];
+
+    self
+  }
+
+  // keeps the xy plane of the matrix, z is dropped
+  pub fn set_by_mat4(&mut self, mat: &Mat4) -> &mut Self {
+    self.m00 = mat.values[MAT4_M00];
+    self.m01 = mat.values[MAT4_M01];
+    self.m02 = mat.values[MAT4_M03];
+    self.m10 = mat.values[MAT4_M10];
+    self.m11 = mat.values[MAT4_M11];
+    self.m12 = mat.values[MAT4_M13];
+
+    self
+  }
+
+  pub fn to_mat3(&self) -> Mat3 {
+    let mut mat = Mat3::new();
+    mat.values[MAT3_M00] = self.m00;
+    mat.values[MAT3_M01] = self.m01;
+    mat.values[MAT3_M02] = self.m02;
+    mat.values[MAT3_M10] = self.m10;
+    mat.values[MAT3_M11] = self.m11;
+    mat.values[MAT3_M12] = self.m12;
+
+    mat
+  }
+
+  pub fn to_mat4(&self) -> Mat4 {
+    let mut mat = Mat4::new();
+    mat.values[MAT4_M00] = self.m00;
+    mat.values[MAT4_M01] = self.m01;
+    mat.values[MAT4_M03] = self.m02;
+    mat.values[MAT4_M10] = self.m10;
+    mat.values[MAT4_M11] = self.m11;
+    mat.values[MAT4_M13] = self.m12;
+
+    mat
+  }
+
+  pub fn set_to_translation(&mut self, x: f32, y: f32) -> &mut Self {
+    self.m00 = 1.0;
+    self.m01 = 0.0;
+    self.m02 = x;
+    self.m10 = 0.0;
+    self.m11 = 1.0;
+    self.m12 = y;
+
+    self
+  }
+
+  pub fn set_to_scaling(&mut self, x: f32, y: f32) -> &mut Self {
+    self.m00 = x;
+    self.m01 = 0.0;
+    self.m02 = 0.0;
+    self.m10 = 0.0;
+    self.m11 = y;
+    self.m12 = 0.0;
+
+    self
+  }
+
+  pub fn set_to_rotation_rad(&mut self, radians: f32) -> &mut Self {
+    let cos = radians.cos();
+    let sin = radians.sin();
+
+    self.m00 = cos;
+    self.m01 = -sin;
+    self.m02 = 0.0;
+    self.m10 = sin;
+    self.m11 = cos;
+    self.m12 = 0.0;
+
+    self
+  }
+
+  pub fn set_to_rotation_deg(&mut self, degrees: f32) -> &mut Self {
+    self.set_to_rotation_rad(degrees.to_radians())
+  }
+
+  pub fn set_to_shearing(&mut self, x: f32, y: f32) -> &mut Self {
+    self.m00 = 1.0;
+    self.m01 = x;
+    self.m02 = 0.0;
+    self.m10 = y;
+    self.m11 = 1.0;
+    self.m12 = 0.0;
+
+    self
+  }
+
+  // translation * rotation * scale, the usual local transform of a scene graph node
+  pub fn set_to_trn_rot_scl(
+    &mut self,
+    x: f32,
+    y: f32,
+    radians: f32,
+    scale_x: f32,
+    scale_y: f32,
+  ) -> &mut Self {
+    self.m02 = x;
+    self.m12 = y;
+
+    match radians == 0.0 {
+      true => {
+        self.m00 = scale_x;
+        self.m01 = 0.0;
+        self.m10 = 0.0;
+        self.m11 = scale_y;
+      }
+      false => {
+        let cos = radians.cos();
+        let sin = radians.sin();
+
+        self.m00 = cos * scale_x;
+        self.m01 = -sin * scale_y;
+        self.m10 = sin * scale_x;
+        self.m11 = cos * scale_y;
+      }
+    }
+
+    self
+  }
+
+  // translate, rotate, scale and shear post-multiply like their Mat3 counterparts,
+  // the operation applies to points before the existing transform does
+
+  pub fn translate(&mut self, x: f32, y: f32) -> &mut Self {
+    self.m02 += self.m00 * x + self.m01 * y;
+    self.m12 += self.m10 * x + self.m11 * y;
+
+    self
+  }
+
+  pub fn rotate_rad(&mut self, radians: f32) -> &mut Self {
+    if radians == 0.0 {
+      return self;
+    }
+    let cos = radians.cos();
+    let sin = radians.sin();
+
+    let tmp00 = self.m00 * cos + self.m01 * sin;
+    let tmp01 = self.m01 * cos - self.m00 * sin;
+    let tmp10 = self.m10 * cos + self.m11 * sin;
+    let tmp11 = self.m11 * cos - self.m10 * sin;
+
+    self.m00 = tmp00;
+    self.m01 = tmp01;
+    self.m10 = tmp10;
+    self.m11 = tmp11;
+
+    self
+  }
+
+  pub fn rotate_deg(&mut self, degrees: f32) -> &mut Self {
+    self.rotate_rad(degrees.to_radians())
+  }
+
+  pub fn scale(&mut self, x: f32, y: f32) -> &mut Self {
+    self.m00 *= x;
+    self.m01 *= y;
+    self.m10 *= x;
+    self.m11 *= y;
+
+    self
+  }
+
+  pub fn shear(&mut self, x: f32, y: f32) -> &mut Self {
+    let tmp00 = self.m00 + y * self.m01;
+    let tmp01 = self.m01 + x * self.m00;
+    let tmp10 = self.m10 + y * self.m11;
+    let tmp11 = self.m11 + x * self.m10;
+
+    self.m00 = tmp00;
+    self.m01 = tmp01;
+    self.m10 = tmp10;
+    self.m11 = tmp11;
+
+    self
+  }
+
+  // self = self * other, other applies first
+  pub fn mul(&mut self, other: &Self) -> &mut Self {
+    let tmp00 = self.m00 * other.m00 + self.m01 * other.m10;
+    let tmp01 = self.m00 * other.m01 + self.m01 * other.m11;
+    let tmp02 = self.m00 * other.m02 + self.m01 * other.m12 + self.m02;
+    let tmp10 = self.m10 * other.m00 + self.m11 * other.m10;
+    let tmp11 = self.m10 * other.m01 + self.m11 * other.m11;
+    let tmp12 = self.m10 * other.m02 + self.m11 * other.m12 + self.m12;
+
+    self.m00 = tmp00;
+    self.m01 = tmp01;
+    self.m02 = tmp02;
+    self.m10 = tmp10;
+    self.m11 = tmp11;
+    self.m12 = tmp12;
+
+    self
+  }
+
+  // self = other * self, other applies last
+  pub fn pre_mul(&mut self, other: &Self) -> &mut Self {
+    let tmp00 = other.m00 * self.m00 + other.m01 * self.m10;
+    let tmp01 = other.m00 * self.m01 + other.m01 * self.m11;
+    let tmp02 = other.m00 * self.m02 + other.m01 * self.m12 + other.m02;
+    let tmp10 = other.m10 * self.m00 + other.m11 * self.m10;
+    let tmp11 = other.m10 * self.m01 + other.m11 * self.m11;
+    let tmp12 = other.m10 * self.m02 + other.m11 * self.m12 + other.m12;
+
+    self.m00 = tmp00;
+    self.m01 = tmp01;
+    self.m02 = tmp02;
+    self.m10 = tmp10;
+    self.m11 = tmp11;
+    self.m12 = tmp12;
+
+    self
+  }
+
+  pub fn det(&self) -> f32 {
+    self.m00 * self.m11 - self.m01 * self.m10
+  }
+
+  pub fn inv(&mut self) -> &mut Self {
+    let det = self.det();
+    if det == 0.0 {
+      panic!("Affine2 is singular and cannot be inverted");
+    }
+
+    let inv_det = 1.0 / det;
+
+    let tmp00 = self.m11;
+    let tmp01 = -self.m01;
+    let tmp02 = self.m01 * self.m12 - self.m11 * self.m02;
+    let tmp10 = -self.m10;
+    let tmp11 = self.m00;
+    let tmp12 = self.m10 * self.m02 - self.m00 * self.m12;
+
+    self.m00 = inv_det * tmp00;
+    self.m01 = inv_det * tmp01;
+    self.m02 = inv_det * tmp02;
+    self.m10 = inv_det * tmp10;
+    self.m11 = inv_det * tmp11;
+    self.m12 = inv_det * tmp12;
+
+    self
+  }
+
+  pub fn get_translation<'a>(&self, output: &'a mut Vec2) -> &'a mut Vec2 {
+    output.x = self.m02;
+    output.y = self.m12;
+
+    output
+  }
+
+  // true when only translation is applied, which lets callers skip the full transform
+  pub fn is_translation(&self) -> bool {
+    self.m00 == 1.0 && self.m11 == 1.0 && self.m01 == 0.0 && self.m10 == 0.0
+  }
+
+  pub fn apply_to<'a>(&self, point: &'a mut Vec2) -> &'a mut Vec2 {
+    let x = self.m00 * point.x + self.m01 * point.y + self.m02;
+    let y = self.m10 * point.x + self.m11 * point.y + self.m12;
+    point.x = x;
+    point.y = y;
+
+    point
+  }
+}
+
+impl Default for Affine2 {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::f32::consts::FRAC_PI_2;
+
+  use super::*;
+
+  fn assert_close(actual: &Affine2, expected: &Affine2) {
+    let pairs = [
+      (actual.m00, expected.m00),
+      (actual.m01, expected.m01),
+      (actual.m02, expected.m02),
+      (actual.m10, expected.m10),
+      (actual.m11, expected.m11),
+      (actual.m12, expected.m12),
+    ];
+    for (a, b) in pairs {
+      assert!((a - b).abs() < 1e-5, "{:?} != {:?}", actual, expected);
+    }
+  }
+
+  fn apply(transform: &Affine2, x: f32, y: f32) -> (f32, f32) {
+    let mut point = Vec2::new(x, y);
+    transform.apply_to(&mut point);
+    (point.x, point.y)
+  }
+
+  fn assert_point(actual: (f32, f32), expected: (f32, f32)) {
+    assert!(
+      (actual.0 - expected.0).abs() < 1e-5 && (actual.1 - expected.1).abs() < 1e-5,
+      "{:?} != {:?}",
+      actual,
+      expected
+    );
+  }
+
+  #[test]
+  fn identity_leaves_points_alone() {
+    let identity = Affine2::new();
+    assert!(identity.is_idt());
+    assert_eq!(apply(&identity, 3., -4.), (3., -4.));
+
+    let mut transform = *Affine2::new().set_to_trn_rot_scl(5., 6., 1., 2., 3.);
+    let copy = transform;
+    assert_eq!(*transform.mul(&identity), copy);
+    assert_eq!(*transform.pre_mul(&identity), copy);
+    assert!(transform.idt().is_idt());
+  }
+
+  #[test]
+  fn inverse_undoes_the_transform() {
+    let transform = *Affine2::new().set_to_trn_rot_scl(5., -6., 0.7, 2., 0.5);
+    let mut inverse = transform;
+    inverse.inv();
+    let (x, y) = apply(&transform, 3., 4.);
+    assert_point(apply(&inverse, x, y), (3., 4.));
+
+    let mut product = inverse;
+    assert_close(product.mul(&transform), &Affine2::new());
+    let mut product = transform;
+    assert_close(product.mul(&inverse), &Affine2::new());
+  }
+
+  #[test]
+  #[should_panic(expected = "singular")]
+  fn singular_matrices_panic_on_inverse() {
+    Affine2::new().set_to_scaling(0., 1.).inv();
+  }
+
+  #[test]
+  fn trn_rot_scl_scales_then_rotates_then_translates() {
+    let transform = *Affine2::new().set_to_trn_rot_scl(10., 20., FRAC_PI_2, 2., 3.);
+    // (1, 1) scales to (2, 3), a quarter turn makes that (-3, 2)
+    assert_point(apply(&transform, 1., 1.), (7., 22.));
+
+    let mut composed = *Affine2::new().set_to_translation(10., 20.);
+    composed.rotate_rad(FRAC_PI_2).scale(2., 3.);
+    assert_close(&composed, &transform);
+
+    // without rotation the fast path has to agree too
+    let unrotated = *Affine2::new().set_to_trn_rot_scl(10., 20., 0., 2., 3.);
+    assert_point(apply(&unrotated, 1., 1.), (12., 23.));
+  }
+
+  #[test]
+  fn mul_applies_the_other_transform_first() {
+    let translation = *Affine2::new().set_to_translation(1., 0.);
+    let scaling = *Affine2::new().set_to_scaling(2., 2.);
+
+    let mut scale_after = scaling;
+    scale_after.mul(&translation);
+    assert_point(apply(&scale_after, 0., 0.), (2., 0.));
+
+    let mut scale_first = scaling;
+    scale_first.pre_mul(&translation);
+    assert_point(apply(&scale_first, 0., 0.), (1., 0.));
+
+    // composing matches applying one after the other
+    let rotation = *Affine2::new().set_to_rotation_deg(30.);
+    let mut composed = rotation;
+    composed.mul(&scale_first);
+    let (x, y) = apply(&scale_first, 3., -2.);
+    assert_point(apply(&composed, 3., -2.), apply(&rotation, x, y));
+  }
+}
@@ -347,21 +347,22 @@ impl Mat4 {
         - v[MAT4_M10] * v[MAT4_M01] * v[MAT4_M22]
         - v[MAT4_M20] * v[MAT4_M11] * v[MAT4_M02]);
 
+    // t holds the cofactors in place, the inverse is their transpose
     v[MAT4_M00] = t[MAT4_M00];
-    v[MAT4_M01] = t[MAT4_M01];
-    v[MAT4_M02] = t[MAT4_M02];
-    v[MAT4_M03] = t[MAT4_M03];
-    v[MAT4_M10] = t[MAT4_M10];
+    v[MAT4_M01] = t[MAT4_M10];
+    v[MAT4_M02] = t[MAT4_M20];
+    v[MAT4_M03] = t[MAT4_M30];
+    v[MAT4_M10] = t[MAT4_M01];
     v[MAT4_M11] = t[MAT4_M11];
-    v[MAT4_M12] = t[MAT4_M12];
-    v[MAT4_M13] = t[MAT4_M13];
-    v[MAT4_M20] = t[MAT4_M20];
-    v[MAT4_M21] = t[MAT4_M21];
+    v[MAT4_M12] = t[MAT4_M21];
+    v[MAT4_M13] = t[MAT4_M31];
+    v[MAT4_M20] = t[MAT4_M02];
+    v[MAT4_M21] = t[MAT4_M12];
     v[MAT4_M22] = t[MAT4_M22];
-    v[MAT4_M23] = t[MAT4_M23];
-    v[MAT4_M30] = t[MAT4_M30];
-    v[MAT4_M31] = t[MAT4_M31];
-    v[MAT4_M32] = t[MAT4_M32];
+    v[MAT4_M23] = t[MAT4_M32];
+    v[MAT4_M30] = t[MAT4_M03];
+    v[MAT4_M31] = t[MAT4_M13];
+    v[MAT4_M32] = t[MAT4_M23];
     v[MAT4_M33] = t[MAT4_M33];
 
     self
@@ -1,3 +1,4 @@
+pub mod affine2;
 pub mod common;
 pub mod mat3;
 pub mod mat4;
@@ -132,6 +132,12 @@ impl Color {
     (a << 24) | (b << 16) | (g << 8) | r
   }
 
+  // the int bits as a float for vertex arrays, the lowest alpha bit is dropped so the bits
+  // are never a NaN that could be changed on the way, shaders scale alpha by 256/255
+  pub fn to_float_bits(&self) -> f32 {
+    f32::from_bits(self.to_int_bits() & 0xfeffffff)
+  }
+
   pub fn lerp<'a>(target: &'a mut Color, from: &Color, to: &Color, t: f32) -> &'a mut Color {
     target.r = from.r + (to.r - from.r) * t;
     target.g = from.g + (to.g - from.g) * t;
@@ -34,6 +34,11 @@ impl FrameCounter {
     delta
   }
 
+  // skip the time spent in the background so the next delta is not huge
+  pub fn reset(&mut self) {
+    self.last_frame = Instant::now();
+  }
+
   pub fn fps(&self) -> u32 {
     self.fps
   }
@@ -1,51 +1,110 @@
 use glow::*;
-use std::rc::Rc;
+use glutin::{
+  dpi::PhysicalSize, event_loop::EventLoop, window::Window, ContextWrapper, PossiblyCurrent,
+};
+use std::{cell::RefCell, rc::Rc};
 
-use crate::{gdx::misc::frame_counter::FrameCounter, CommonAppHandler};
+use crate::{
+  gdx::misc::frame_counter::FrameCounter,
+  input::{Input, TouchPointers, BUTTON_LEFT, BUTTON_MIDDLE, BUTTON_RIGHT, MOUSE_POINTER},
+  AppCallbacks, CommonAppHandler, ScreenSize,
+};
 
-struct GlutinAppHandler {
-  update: Option<Box<dyn FnMut(&Rc<Context>, f32) -> ()>>,
-  width: f32,
-  height: f32,
+// touchpads report pixels, everything else reports lines
+const PIXELS_PER_SCROLL_LINE: f32 = 16.;
+
+pub(crate) struct GlutinAppHandler {
+  callbacks: AppCallbacks,
+  size: ScreenSize,
   frame_counter: FrameCounter,
+  input: Rc<RefCell<Input>>,
 }
 
 impl GlutinAppHandler {
-  pub fn new(width: f32, height: f32) -> Self {
+  pub fn new(window: &Window) -> Self {
     Self {
-      update: None,
-      width,
-      height,
+      callbacks: AppCallbacks::new(),
+      size: screen_size(window.inner_size(), window.scale_factor()),
       frame_counter: FrameCounter::new(),
+      input: Rc::new(RefCell::new(Input::new())),
     }
   }
   pub fn update(&mut self, gl: &Rc<Context>) {
     let delta = self.frame_counter.update();
-    match self.update {
-      Some(ref mut update) => {
-        update(gl, delta);
-      }
-      None => (),
+    self.callbacks.update(gl, delta);
+    self.input.borrow_mut().end_frame();
+  }
+  pub fn resize(&mut self, gl: &Rc<Context>, size: ScreenSize) {
+    if self.size == size {
+      return;
     }
+    self.size = size;
+    unsafe {
+      gl.viewport(
+        0,
+        0,
+        size.physical_width as i32,
+        size.physical_height as i32,
+      );
+    }
+    self.callbacks.resize(gl, &size);
+  }
+  pub fn pause(&mut self, gl: &Rc<Context>) {
+    self.callbacks.pause(gl);
+  }
+  pub fn resume(&mut self, gl: &Rc<Context>) {
+    self.frame_counter.reset();
+    self.callbacks.resume(gl);
+  }
+  pub fn dispose(&mut self, gl: &Rc<Context>) {
+    self.callbacks.dispose(gl);
   }
 }
 
+fn screen_size(physical_size: PhysicalSize<u32>, scale_factor: f64) -> ScreenSize {
+  ScreenSize::from_physical(
+    physical_size.width as f32,
+    physical_size.height as f32,
+    scale_factor as f32,
+  )
+}
+
 impl CommonAppHandler for GlutinAppHandler {
   fn set_update_fn(&mut self, update: Box<dyn FnMut(&Rc<Context>, f32) -> () + 'static>) {
-    self.update = Some(update);
+    self.callbacks.set_update_fn(update);
   }
 
-  fn get_width(&self) -> f32 {
-    self.width
+  fn set_resize_fn(&mut self, resize: Box<dyn FnMut(&Rc<Context>, &ScreenSize) -> () + 'static>) {
+    self.callbacks.set_resize_fn(resize);
   }
 
-  fn get_height(&self) -> f32 {
-    self.height
+  fn set_pause_fn(&mut self, pause: Box<dyn FnMut(&Rc<Context>) -> () + 'static>) {
+    self.callbacks.set_pause_fn(pause);
+  }
+
+  fn set_resume_fn(&mut self, resume: Box<dyn FnMut(&Rc<Context>) -> () + 'static>) {
+    self.callbacks.set_resume_fn(resume);
+  }
+
+  fn set_dispose_fn(&mut self, dispose: Box<dyn FnOnce(&Rc<Context>) -> () + 'static>) {
+    self.callbacks.set_dispose_fn(dispose);
+  }
+
+  fn get_screen_size(&self) -> ScreenSize {
+    self.size
   }
 
   fn get_file_path(&self, file_name: &str) -> String {
     format!("assets/{}", file_name)
   }
+
+  fn get_input(&self) -> Rc<RefCell<Input>> {
+    Rc::clone(&self.input)
+  }
+
+  fn is_hot_reload_enabled(&self) -> bool {
+    true
+  }
 }
 
 pub fn start_glutin<F>(init_func: F)
@@ -69,35 +128,129 @@ where
     };
 
     let gl = Rc::new(gl);
-    let mut app = GlutinAppHandler::new(325.0, 768.0);
+    let mut app = GlutinAppHandler::new(window.window());
     init_func(&mut app, &gl);
-    {
-      use glutin::event::{Event, WindowEvent};
-      use glutin::event_loop::ControlFlow;
-
-      event_loop.run(move |event, _, control_flow| {
-        *control_flow = ControlFlow::Wait;
-        match event {
-          Event::LoopDestroyed => {
-            return;
-          }
-          Event::MainEventsCleared => {
-            window.window().request_redraw();
+    run_event_loop(gl, window, event_loop, app);
+  }
+}
+
+// shared by every winit based backend, so they all tick the same way
+pub(crate) fn run_event_loop(
+  gl: Rc<Context>,
+  window: ContextWrapper<PossiblyCurrent, Window>,
+  event_loop: EventLoop<()>,
+  mut app: GlutinAppHandler,
+) -> ! {
+  use glutin::event::{
+    ElementState, Event, KeyboardInput, MouseButton, MouseScrollDelta, TouchPhase, WindowEvent,
+  };
+  use glutin::event_loop::ControlFlow;
+
+  let input = Rc::clone(&app.input);
+  let mut touches = TouchPointers::new();
+
+  event_loop.run(move |event, _, control_flow| {
+    *control_flow = ControlFlow::Wait;
+    match event {
+      Event::LoopDestroyed => {
+        // the context is still current here, it is not once the closure is dropped
+        app.dispose(&gl);
+        return;
+      }
+      Event::Suspended => app.pause(&gl),
+      Event::Resumed => app.resume(&gl),
+      Event::MainEventsCleared => {
+        window.window().request_redraw();
+      }
+      Event::RedrawRequested(_) => {
+        app.update(&gl);
+        window.swap_buffers().unwrap();
+      }
+      Event::WindowEvent { ref event, .. } => match event {
+        WindowEvent::Resized(physical_size) => {
+          window.resize(*physical_size);
+          let scale_factor = window.window().scale_factor();
+          app.resize(&gl, screen_size(*physical_size, scale_factor));
+        }
+        WindowEvent::ScaleFactorChanged {
+          scale_factor,
+          new_inner_size,
+        } => {
+          window.resize(**new_inner_size);
+          app.resize(&gl, screen_size(**new_inner_size, *scale_factor));
+        }
+        WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
+        WindowEvent::Focused(false) => app.pause(&gl),
+        WindowEvent::Focused(true) => app.resume(&gl),
+        WindowEvent::CursorMoved { position, .. } => {
+          let position = position.to_logical::<f32>(window.window().scale_factor());
+          input
+            .borrow_mut()
+            .pointer_move(MOUSE_POINTER, position.x, position.y);
+        }
+        WindowEvent::MouseInput { state, button, .. } => {
+          let button = match button {
+            MouseButton::Left => BUTTON_LEFT,
+            MouseButton::Middle => BUTTON_MIDDLE,
+            MouseButton::Right => BUTTON_RIGHT,
+            // glutin counts the other buttons from 0 too, they go after the named ones
+            MouseButton::Other(button) => BUTTON_RIGHT + 1 + *button as u32,
+          };
+          let mut input = input.borrow_mut();
+          let x = input.get_pointer_x(MOUSE_POINTER);
+          let y = input.get_pointer_y(MOUSE_POINTER);
+          match state {
+            ElementState::Pressed => input.pointer_down(MOUSE_POINTER, button, x, y),
+            ElementState::Released => input.pointer_up(MOUSE_POINTER, button, x, y),
           }
-          Event::RedrawRequested(_) => {
-            app.update(&gl);
-            window.swap_buffers().unwrap();
+        }
+        WindowEvent::MouseWheel { delta, .. } => {
+          let (x, y) = match delta {
+            MouseScrollDelta::LineDelta(x, y) => (*x, *y),
+            MouseScrollDelta::PixelDelta(position) => {
+              let position = position.to_logical::<f32>(window.window().scale_factor());
+              (
+                position.x / PIXELS_PER_SCROLL_LINE,
+                position.y / PIXELS_PER_SCROLL_LINE,
+              )
+            }
+          };
+          input.borrow_mut().scroll(x, y);
+        }
+        WindowEvent::KeyboardInput {
+          input:
+            KeyboardInput {
+              state,
+              virtual_keycode: Some(key),
+              ..
+            },
+          ..
+        } => {
+          // key names are the winit variant names, e.g. "A", "Key1", "Space", "Left"
+          let key = format!("{:?}", key);
+          match state {
+            ElementState::Pressed => input.borrow_mut().key_down(&key),
+            ElementState::Released => input.borrow_mut().key_up(&key),
           }
-          Event::WindowEvent { ref event, .. } => match event {
-            WindowEvent::Resized(physical_size) => {
-              window.resize(*physical_size);
+        }
+        WindowEvent::Touch(touch) => {
+          let pointer = touches.pointer_for(touch.id);
+          let position = touch
+            .location
+            .to_logical::<f32>(window.window().scale_factor());
+          let mut input = input.borrow_mut();
+          match touch.phase {
+            TouchPhase::Started => input.pointer_down(pointer, BUTTON_LEFT, position.x, position.y),
+            TouchPhase::Moved => input.pointer_move(pointer, position.x, position.y),
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+              input.pointer_up(pointer, BUTTON_LEFT, position.x, position.y);
+              touches.release(pointer);
             }
-            WindowEvent::CloseRequested => *control_flow = ControlFlow::Exit,
-            _ => (),
-          },
-          _ => (),
+          }
         }
-      });
+        _ => (),
+      },
+      _ => (),
     }
-  }
+  })
 }
@@ -0,0 +1,147 @@
+use glow::*;
+use glutin::{dpi::PhysicalSize, platform::unix::HeadlessContextExt, ContextBuilder};
+use std::{cell::RefCell, rc::Rc};
+
+use crate::{input::Input, AppCallbacks, CommonAppHandler, ScreenSize};
+
+pub const HEADLESS_FRAMES_ENV: &str = "MY_GAME_HEADLESS_FRAMES";
+
+#[derive(Debug, Clone)]
+pub struct HeadlessOptions {
+  pub width: u32,
+  pub height: u32,
+  pub frames: u32,
+  pub delta: f32,
+  // where `get_file_path` and so main.js are looked up
+  pub assets: String,
+}
+
+impl Default for HeadlessOptions {
+  fn default() -> Self {
+    Self {
+      width: 325,
+      height: 768,
+      frames: 60,
+      delta: 1.0 / 60.0,
+      assets: "assets".to_string(),
+    }
+  }
+}
+
+impl HeadlessOptions {
+  // MY_GAME_HEADLESS_FRAMES=<frames> switches init_game to this backend on CI
+  pub fn from_env() -> Option<Self> {
+    let frames = std::env::var(HEADLESS_FRAMES_ENV).ok()?.parse().ok()?;
+    Some(Self {
+      frames,
+      ..Self::default()
+    })
+  }
+}
+
+struct HeadlessAppHandler {
+  callbacks: AppCallbacks,
+  size: ScreenSize,
+  delta: f32,
+  assets: String,
+  input: Rc<RefCell<Input>>,
+}
+
+impl HeadlessAppHandler {
+  pub fn new(width: f32, height: f32, delta: f32, assets: String) -> Self {
+    Self {
+      callbacks: AppCallbacks::new(),
+      size: ScreenSize::from_physical(width, height, 1.),
+      delta,
+      assets,
+      input: Rc::new(RefCell::new(Input::new())),
+    }
+  }
+
+  pub fn update(&mut self, gl: &Rc<Context>) {
+    self.callbacks.update(gl, self.delta);
+    self.input.borrow_mut().end_frame();
+  }
+}
+
+impl CommonAppHandler for HeadlessAppHandler {
+  fn set_update_fn(&mut self, update: Box<dyn FnMut(&Rc<Context>, f32) -> () + 'static>) {
+    self.callbacks.set_update_fn(update);
+  }
+
+  // the offscreen framebuffer never changes size and never goes to the background,
+  // only dispose is ever called
+  fn set_resize_fn(&mut self, _resize: Box<dyn FnMut(&Rc<Context>, &ScreenSize) -> () + 'static>) {}
+
+  fn set_pause_fn(&mut self, _pause: Box<dyn FnMut(&Rc<Context>) -> () + 'static>) {}
+
+  fn set_resume_fn(&mut self, _resume: Box<dyn FnMut(&Rc<Context>) -> () + 'static>) {}
+
+  fn set_dispose_fn(&mut self, dispose: Box<dyn FnOnce(&Rc<Context>) -> () + 'static>) {
+    self.callbacks.set_dispose_fn(dispose);
+  }
+
+  fn get_screen_size(&self) -> ScreenSize {
+    self.size
+  }
+
+  fn get_file_path(&self, file_name: &str) -> String {
+    format!("{}/{}", self.assets, file_name)
+  }
+
+  fn get_input(&self) -> Rc<RefCell<Input>> {
+    Rc::clone(&self.input)
+  }
+}
+
+// renders `options.frames` frames into an OSMesa software framebuffer and returns the
+// RGBA pixels of the last one, bottom row first like `glReadPixels`
+pub fn run_headless<F>(options: HeadlessOptions, init_func: F) -> Vec<u8>
+where
+  F: FnOnce(&mut dyn CommonAppHandler, &Rc<Context>) -> (),
+{
+  unsafe {
+    let context = ContextBuilder::new()
+      .build_osmesa(PhysicalSize::new(options.width, options.height))
+      .expect("failed to create an OSMesa context, is libOSMesa installed?")
+      .make_current()
+      .unwrap();
+    let gl = glow::Context::from_loader_function(|s| context.get_proc_address(s) as *const _);
+    let gl = Rc::new(gl);
+    gl.viewport(0, 0, options.width as i32, options.height as i32);
+
+    let mut app = HeadlessAppHandler::new(
+      options.width as f32,
+      options.height as f32,
+      options.delta,
+      options.assets,
+    );
+    init_func(&mut app, &gl);
+    for _ in 0..options.frames {
+      app.update(&gl);
+    }
+    gl.finish();
+
+    let mut pixels = vec![0; (options.width * options.height * 4) as usize];
+    gl.read_pixels(
+      0,
+      0,
+      options.width as i32,
+      options.height as i32,
+      RGBA,
+      UNSIGNED_BYTE,
+      PixelPackData::Slice(&mut pixels),
+    );
+    app.callbacks.dispose(&gl);
+    pixels
+  }
+}
+
+pub fn start_headless<F>(options: HeadlessOptions, init_func: F)
+where
+  F: FnOnce(&mut dyn CommonAppHandler, &Rc<Context>) -> () + 'static,
+{
+  let frames = options.frames;
+  let _ = run_headless(options, init_func);
+  println!("headless: rendered {} frames", frames);
+}
@@ -0,0 +1,264 @@
+use std::collections::{HashMap, HashSet};
+
+use rquickjs::IntoJs;
+
+// the mouse always reports as pointer 0, touches start at 1, see `TouchPointers`
+pub const MOUSE_POINTER: u32 = 0;
+
+pub const BUTTON_LEFT: u32 = 0;
+pub const BUTTON_MIDDLE: u32 = 1;
+pub const BUTTON_RIGHT: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq, IntoJs)]
+#[quickjs(tag = "type", rename_all = "camelCase")]
+pub enum InputEvent {
+  PointerDown {
+    pointer: u32,
+    button: u32,
+    x: f32,
+    y: f32,
+  },
+  PointerMove {
+    pointer: u32,
+    x: f32,
+    y: f32,
+  },
+  PointerUp {
+    pointer: u32,
+    button: u32,
+    x: f32,
+    y: f32,
+  },
+  KeyDown {
+    key: String,
+  },
+  KeyUp {
+    key: String,
+  },
+  // in lines, positive y scrolls up
+  Scroll {
+    x: f32,
+    y: f32,
+  },
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PointerState {
+  pub x: f32,
+  pub y: f32,
+  pub down: bool,
+}
+
+// maps the platform's ids of ongoing touches to pointers, the lowest free one is reused so
+// pointers stay small whatever the platform hands out
+pub struct TouchPointers<T> {
+  slots: Vec<Option<T>>,
+}
+
+impl<T: PartialEq> TouchPointers<T> {
+  pub fn new() -> Self {
+    Self { slots: vec![] }
+  }
+
+  pub fn pointer_for(&mut self, touch: T) -> u32 {
+    let index = match self
+      .slots
+      .iter()
+      .position(|slot| slot.as_ref() == Some(&touch))
+    {
+      Some(index) => index,
+      None => match self.slots.iter().position(Option::is_none) {
+        Some(index) => {
+          self.slots[index] = Some(touch);
+          index
+        }
+        None => {
+          self.slots.push(Some(touch));
+          self.slots.len() - 1
+        }
+      },
+    };
+    MOUSE_POINTER + 1 + index as u32
+  }
+
+  // once the touch ended, its pointer goes to the next one
+  pub fn release(&mut self, pointer: u32) {
+    let index = pointer.checked_sub(MOUSE_POINTER + 1);
+    if let Some(slot) = index.and_then(|index| self.slots.get_mut(index as usize)) {
+      *slot = None;
+    }
+  }
+}
+
+impl<T: PartialEq> Default for TouchPointers<T> {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+// events are queued between two frames, `end_frame` is called by the backend after update
+#[derive(Default)]
+pub struct Input {
+  events: Vec<InputEvent>,
+  pointers: HashMap<u32, PointerState>,
+  // pointer and button pairs, a pointer stays down until all of its buttons are released
+  buttons: HashSet<(u32, u32)>,
+  keys: HashSet<String>,
+  scroll_x: f32,
+  scroll_y: f32,
+}
+
+impl Input {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn events(&self) -> &[InputEvent] {
+    &self.events
+  }
+
+  pub fn is_key_pressed(&self, key: &str) -> bool {
+    self.keys.contains(key)
+  }
+
+  pub fn is_pointer_down(&self, pointer: u32) -> bool {
+    self
+      .pointers
+      .get(&pointer)
+      .map_or(false, |state| state.down)
+  }
+
+  pub fn get_pointer(&self, pointer: u32) -> Option<PointerState> {
+    self.pointers.get(&pointer).copied()
+  }
+
+  pub fn get_pointer_x(&self, pointer: u32) -> f32 {
+    self.pointers.get(&pointer).map_or(0., |state| state.x)
+  }
+
+  pub fn get_pointer_y(&self, pointer: u32) -> f32 {
+    self.pointers.get(&pointer).map_or(0., |state| state.y)
+  }
+
+  pub fn get_scroll_x(&self) -> f32 {
+    self.scroll_x
+  }
+
+  pub fn get_scroll_y(&self) -> f32 {
+    self.scroll_y
+  }
+
+  pub fn pointer_down(&mut self, pointer: u32, button: u32, x: f32, y: f32) {
+    self.buttons.insert((pointer, button));
+    self
+      .pointers
+      .insert(pointer, PointerState { x, y, down: true });
+    self.events.push(InputEvent::PointerDown {
+      pointer,
+      button,
+      x,
+      y,
+    });
+  }
+
+  pub fn pointer_move(&mut self, pointer: u32, x: f32, y: f32) {
+    let state = self.pointers.entry(pointer).or_default();
+    state.x = x;
+    state.y = y;
+    self.events.push(InputEvent::PointerMove { pointer, x, y });
+  }
+
+  // every button queues its own event, the pointer only goes up with the last one
+  pub fn pointer_up(&mut self, pointer: u32, button: u32, x: f32, y: f32) {
+    self.buttons.remove(&(pointer, button));
+    let down = self.buttons.iter().any(|(held, _)| *held == pointer);
+    self.pointers.insert(pointer, PointerState { x, y, down });
+    self.events.push(InputEvent::PointerUp {
+      pointer,
+      button,
+      x,
+      y,
+    });
+  }
+
+  pub fn key_down(&mut self, key: &str) {
+    // key repeat sends KeyDown again, only queue the first one
+    if self.keys.insert(key.to_string()) {
+      self.events.push(InputEvent::KeyDown {
+        key: key.to_string(),
+      });
+    }
+  }
+
+  pub fn key_up(&mut self, key: &str) {
+    if self.keys.remove(key) {
+      self.events.push(InputEvent::KeyUp {
+        key: key.to_string(),
+      });
+    }
+  }
+
+  pub fn scroll(&mut self, x: f32, y: f32) {
+    self.scroll_x += x;
+    self.scroll_y += y;
+    self.events.push(InputEvent::Scroll { x, y });
+  }
+
+  pub fn end_frame(&mut self) {
+    self.events.clear();
+    self.scroll_x = 0.;
+    self.scroll_y = 0.;
+    self
+      .pointers
+      .retain(|pointer, state| state.down || *pointer == MOUSE_POINTER);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn the_mouse_stays_down_until_every_button_is_released() {
+    let mut input = Input::new();
+    input.pointer_down(MOUSE_POINTER, BUTTON_LEFT, 1., 2.);
+    input.pointer_down(MOUSE_POINTER, BUTTON_RIGHT, 1., 2.);
+    input.pointer_up(MOUSE_POINTER, BUTTON_RIGHT, 3., 4.);
+    assert!(input.is_pointer_down(MOUSE_POINTER));
+    input.pointer_up(MOUSE_POINTER, BUTTON_LEFT, 5., 6.);
+    assert!(!input.is_pointer_down(MOUSE_POINTER));
+    assert_eq!(input.events().len(), 4);
+    assert_eq!(
+      input.events()[2],
+      InputEvent::PointerUp {
+        pointer: MOUSE_POINTER,
+        button: BUTTON_RIGHT,
+        x: 3.,
+        y: 4.,
+      }
+    );
+  }
+
+  #[test]
+  fn touches_get_pointers_apart_from_the_mouse() {
+    let mut touches = TouchPointers::new();
+    assert_eq!(touches.pointer_for(0u64), 1);
+    assert_eq!(touches.pointer_for(u64::MAX), 2);
+    assert_eq!(touches.pointer_for(0), 1);
+    touches.release(1);
+    assert_eq!(touches.pointer_for(7), 1);
+    assert_eq!(touches.pointer_for(u64::MAX), 2);
+  }
+
+  #[test]
+  fn touches_release_independently() {
+    let mut input = Input::new();
+    input.pointer_down(1, BUTTON_LEFT, 0., 0.);
+    input.pointer_down(2, BUTTON_LEFT, 0., 0.);
+    input.pointer_up(1, BUTTON_LEFT, 0., 0.);
+    assert!(!input.is_pointer_down(1));
+    assert!(input.is_pointer_down(2));
+    input.end_frame();
+    assert_eq!(input.get_pointer(1).map(|state| state.down), None);
+  }
+}
@@ -1,10 +1,12 @@
+use std::cell::RefCell;
 use std::ffi::{c_void, CStr, CString};
 use std::os::raw::c_char;
 use std::ptr;
 use std::rc::Rc;
 
 use crate::gdx::misc::frame_counter::FrameCounter;
-use crate::CommonAppHandler;
+use crate::input::{Input, TouchPointers, BUTTON_LEFT};
+use crate::{AppCallbacks, CommonAppHandler, ScreenSize};
 use objc::declare::ClassDecl;
 use objc::runtime::*;
 use objc::*;
@@ -136,9 +138,78 @@ struct View {
   _gles2: bool,
 }
 
+enum TouchPhase {
+  Began,
+  Moved,
+  Ended,
+}
+
+struct TouchState {
+  input: Rc<RefCell<Input>>,
+  // UITouch objects stay the same for the whole gesture
+  touches: TouchPointers<ObjcId>,
+}
+
+unsafe fn handle_touches(this: &Object, touches: ObjcId, phase: TouchPhase) {
+  let state_ptr: *mut c_void = *this.get_ivar("touch_state");
+  if state_ptr.is_null() {
+    return;
+  }
+  let state = &mut *(state_ptr as *mut TouchState);
+  let view = this as *const Object as ObjcId;
+
+  let touches: ObjcId = msg_send![touches, allObjects];
+  let count: usize = msg_send![touches, count];
+  for i in 0..count {
+    let touch: ObjcId = msg_send![touches, objectAtIndex: i];
+    let location: NSPoint = msg_send![touch, locationInView: view];
+    let (x, y) = (location.x as f32, location.y as f32);
+    let pointer = state.touches.pointer_for(touch);
+    let mut input = state.input.borrow_mut();
+    match phase {
+      TouchPhase::Began => input.pointer_down(pointer, BUTTON_LEFT, x, y),
+      TouchPhase::Moved => input.pointer_move(pointer, x, y),
+      TouchPhase::Ended => {
+        input.pointer_up(pointer, BUTTON_LEFT, x, y);
+        state.touches.release(pointer);
+      }
+    }
+  }
+}
+
 pub fn define_glk_or_mtk_view(superclass: &Class) -> *const Class {
   let mut decl = ClassDecl::new("QuadView", superclass).unwrap();
   decl.add_ivar::<*mut c_void>("display_ptr");
+  decl.add_ivar::<*mut c_void>("touch_state");
+
+  extern "C" fn touches_began(this: &Object, _: Sel, touches: ObjcId, _: ObjcId) {
+    unsafe { handle_touches(this, touches, TouchPhase::Began) }
+  }
+  extern "C" fn touches_moved(this: &Object, _: Sel, touches: ObjcId, _: ObjcId) {
+    unsafe { handle_touches(this, touches, TouchPhase::Moved) }
+  }
+  extern "C" fn touches_ended(this: &Object, _: Sel, touches: ObjcId, _: ObjcId) {
+    unsafe { handle_touches(this, touches, TouchPhase::Ended) }
+  }
+
+  unsafe {
+    decl.add_method(
+      sel!(touchesBegan: withEvent:),
+      touches_began as extern "C" fn(&Object, Sel, ObjcId, ObjcId),
+    );
+    decl.add_method(
+      sel!(touchesMoved: withEvent:),
+      touches_moved as extern "C" fn(&Object, Sel, ObjcId, ObjcId),
+    );
+    decl.add_method(
+      sel!(touchesEnded: withEvent:),
+      touches_ended as extern "C" fn(&Object, Sel, ObjcId, ObjcId),
+    );
+    decl.add_method(
+      sel!(touchesCancelled: withEvent:),
+      touches_ended as extern "C" fn(&Object, Sel, ObjcId, ObjcId),
+    );
+  }
   return decl.register();
 }
 
@@ -172,31 +243,50 @@ unsafe fn create_opengl_view(screen_rect: NSRect, _sample_count: i32, high_dpi:
   msg_send_![glk_view_obj, setUserInteractionEnabled: YES];
   msg_send_![glk_view_obj, setMultipleTouchEnabled: YES];
   if high_dpi {
-    msg_send_![glk_view_obj, setContentScaleFactor: 2.0];
+    // 2.0 on most devices, 3.0 on plus/max models
+    let main_screen: ObjcId = msg_send![class!(UIScreen), mainScreen];
+    let native_scale: f64 = msg_send![main_screen, nativeScale];
+    msg_send_![glk_view_obj, setContentScaleFactor: native_scale];
   } else {
     msg_send_![glk_view_obj, setContentScaleFactor: 1.0];
   }
 
+  let input = Rc::new(RefCell::new(Input::new()));
+  (*glk_view_obj).set_ivar(
+    "touch_state",
+    Box::into_raw(Box::new(TouchState {
+      input: Rc::clone(&input),
+      touches: TouchPointers::new(),
+    })) as *mut c_void,
+  );
+
   let superclass = class!(GLKViewController);
   let mut decl = ClassDecl::new("MyGLKViewController", superclass).unwrap();
   decl.add_ivar::<*mut c_void>("game");
-  decl.add_ivar::<*mut c_void>("size_ptr");
+  decl.add_ivar::<*mut c_void>("input_ptr");
 
   extern "C" fn update(this: &mut Object, _: Sel) {
     unsafe {
+      // bounds change on rotation, there is no callback we can rely on for GLKView so poll it
+      let view: ObjcId = msg_send![this, view];
+      let size = view_screen_size(view);
+
       let mut game_ptr: *mut c_void = *this.get_ivar("game");
       if game_ptr.is_null() {
-        let size_ptr: *const c_void = *this.get_ivar("size_ptr");
-        let size_ptr = size_ptr as *const NSSize;
-        let size = *size_ptr;
+        let input_ptr: *const c_void = *this.get_ivar("input_ptr");
+        let input = Rc::clone(&*(input_ptr as *const Rc<RefCell<Input>>));
+
+        let eagl_context: ObjcId = msg_send![view, context];
 
         let f = RUN_ARGS.take().unwrap();
-        let game = AppHandler::new(size.width as f32, size.height as f32, f);
+        let game = AppHandler::new(size, input, eagl_context, f);
         this.set_ivar("game", Box::into_raw(Box::new(game)) as *mut c_void);
         game_ptr = *this.get_ivar("game");
+        APP_HANDLER = game_ptr as *mut AppHandler;
       }
 
       let game = &mut *(game_ptr as *mut AppHandler);
+      game.resize(size);
       game.update();
     };
   }
@@ -209,10 +299,7 @@ unsafe fn create_opengl_view(screen_rect: NSRect, _sample_count: i32, high_dpi:
 
   let view_ctrl_obj: ObjcId = msg_send![my_controller_class, alloc];
   let view_ctrl_obj: ObjcId = msg_send![view_ctrl_obj, init];
-  (*view_ctrl_obj).set_ivar(
-    "size_ptr",
-    Box::into_raw(Box::new(screen_rect.size)) as *const c_void,
-  );
+  (*view_ctrl_obj).set_ivar("input_ptr", Box::into_raw(Box::new(input)) as *const c_void);
 
   msg_send_![view_ctrl_obj, setView: glk_view_obj];
   msg_send_![view_ctrl_obj, setPreferredFramesPerSecond:60];
@@ -224,6 +311,16 @@ unsafe fn create_opengl_view(screen_rect: NSRect, _sample_count: i32, high_dpi:
   }
 }
 
+unsafe fn view_screen_size(view: ObjcId) -> ScreenSize {
+  let bounds: NSRect = msg_send![view, bounds];
+  let scale_factor: f64 = msg_send![view, contentScaleFactor];
+  ScreenSize::from_logical(
+    bounds.size.width as f32,
+    bounds.size.height as f32,
+    scale_factor as f32,
+  )
+}
+
 #[allow(dead_code)]
 unsafe fn get_proc_address(name: *const u8) -> *const c_void {
   mod libc {
@@ -279,11 +376,48 @@ fn define_app_delegate() -> *const Class {
     YES
   }
 
+  // GLKViewController already stops calling update while inactive, these only notify the game
+  extern "C" fn will_resign_active(_: &Object, _: Sel, _: ObjcId) {
+    unsafe {
+      if let Some(game) = APP_HANDLER.as_mut() {
+        game.pause();
+      }
+    }
+  }
+
+  extern "C" fn did_become_active(_: &Object, _: Sel, _: ObjcId) {
+    unsafe {
+      if let Some(game) = APP_HANDLER.as_mut() {
+        game.resume();
+      }
+    }
+  }
+
+  extern "C" fn will_terminate(_: &Object, _: Sel, _: ObjcId) {
+    unsafe {
+      if let Some(game) = APP_HANDLER.as_mut() {
+        game.dispose();
+      }
+    }
+  }
+
   unsafe {
     decl.add_method(
       sel!(application: didFinishLaunchingWithOptions:),
       did_finish_launching_with_options as extern "C" fn(&Object, Sel, ObjcId, ObjcId) -> BOOL,
     );
+    decl.add_method(
+      sel!(applicationWillResignActive:),
+      will_resign_active as extern "C" fn(&Object, Sel, ObjcId),
+    );
+    decl.add_method(
+      sel!(applicationDidBecomeActive:),
+      did_become_active as extern "C" fn(&Object, Sel, ObjcId),
+    );
+    decl.add_method(
+      sel!(applicationWillTerminate:),
+      will_terminate as extern "C" fn(&Object, Sel, ObjcId),
+    );
   }
 
   decl.register()
@@ -308,9 +442,10 @@ pub const NIL: ObjcId = 0 as ObjcId;
 struct AppHandler {
   gl: Rc<Context>,
   frame_counter: FrameCounter,
-  width: f32,
-  height: f32,
-  update: Option<Box<dyn FnMut(&Rc<Context>, f32) -> ()>>,
+  size: ScreenSize,
+  input: Rc<RefCell<Input>>,
+  eagl_context: ObjcId,
+  callbacks: AppCallbacks,
 }
 pub const UTF8_ENCODING: usize = 4;
 
@@ -353,7 +488,7 @@ pub fn get_file_path(path: &str) -> String {
 }
 
 impl AppHandler {
-  pub fn new<F>(width: f32, height: f32, f: F) -> Self
+  pub fn new<F>(size: ScreenSize, input: Rc<RefCell<Input>>, eagl_context: ObjcId, f: F) -> Self
   where
     F: FnOnce(&mut dyn CommonAppHandler, &Rc<Context>) -> () + 'static,
   {
@@ -366,51 +501,98 @@ impl AppHandler {
     let mut handler = Self {
       gl: gl.clone(),
       frame_counter: FrameCounter::new(),
-      width,
-      height,
-      update: None,
+      size,
+      input,
+      eagl_context,
+      callbacks: AppCallbacks::new(),
     };
     f(&mut handler, &gl);
     handler
   }
 
-  // TODO: resize logic
+  fn resize(&mut self, size: ScreenSize) {
+    if self.size == size {
+      return;
+    }
+    self.size = size;
+    unsafe {
+      self.gl.viewport(
+        0,
+        0,
+        size.physical_width as i32,
+        size.physical_height as i32,
+      );
+    }
+    self.callbacks.resize(&self.gl, &size);
+  }
 
-  fn set_update_fn<F>(&mut self, update: F)
-  where
-    F: FnMut(&Rc<Context>, f32) -> () + 'static,
-  {
-    self.update = Some(Box::new(update));
+  // app delegate callbacks run outside of GLKView drawing, the context may not be current
+  fn make_current(&self) {
+    unsafe {
+      let _: BOOL = msg_send![class!(EAGLContext), setCurrentContext: self.eagl_context];
+    }
+  }
+
+  fn pause(&mut self) {
+    self.make_current();
+    self.callbacks.pause(&self.gl);
+  }
+
+  fn resume(&mut self) {
+    self.make_current();
+    self.frame_counter.reset();
+    self.callbacks.resume(&self.gl);
+  }
+
+  fn dispose(&mut self) {
+    self.make_current();
+    self.callbacks.dispose(&self.gl);
   }
 
   unsafe fn update(&mut self) {
     let delta = self.frame_counter.update();
 
-    match self.update {
-      Some(ref mut update) => update(&self.gl, delta),
-      None => (),
-    }
+    self.callbacks.update(&self.gl, delta);
+    self.input.borrow_mut().end_frame();
   }
 }
 
 impl CommonAppHandler for AppHandler {
   fn set_update_fn(&mut self, update: Box<dyn FnMut(&Rc<Context>, f32) -> () + 'static>) {
-    self.set_update_fn(update);
+    self.callbacks.set_update_fn(update);
   }
 
-  fn get_width(&self) -> f32 {
-    self.width
+  fn set_resize_fn(&mut self, resize: Box<dyn FnMut(&Rc<Context>, &ScreenSize) -> () + 'static>) {
+    self.callbacks.set_resize_fn(resize);
   }
 
-  fn get_height(&self) -> f32 {
-    self.height
+  fn set_pause_fn(&mut self, pause: Box<dyn FnMut(&Rc<Context>) -> () + 'static>) {
+    self.callbacks.set_pause_fn(pause);
+  }
+
+  fn set_resume_fn(&mut self, resume: Box<dyn FnMut(&Rc<Context>) -> () + 'static>) {
+    self.callbacks.set_resume_fn(resume);
+  }
+
+  fn set_dispose_fn(&mut self, dispose: Box<dyn FnOnce(&Rc<Context>) -> () + 'static>) {
+    self.callbacks.set_dispose_fn(dispose);
+  }
+
+  fn get_screen_size(&self) -> ScreenSize {
+    self.size
   }
 
   fn get_file_path(&self, file_name: &str) -> String {
     get_file_path(file_name)
   }
+
+  fn get_input(&self) -> Rc<RefCell<Input>> {
+    Rc::clone(&self.input)
+  }
 }
 
+static mut APP_HANDLER: *mut AppHandler = ptr::null_mut();
+
 static mut RUN_ARGS: Option<Box<dyn FnOnce(&mut dyn CommonAppHandler, &Rc<Context>) -> ()>> = None;
 
 pub fn start_ios<F>(init_func: F)
@@ -0,0 +1,121 @@
+use rquickjs::{function::This, CaughtError, Ctx, Error, Function, Undefined, Value};
+use std::{cell::RefCell, fmt, rc::Rc};
+
+// what happens once a script throws, the exception is logged whatever the policy
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorPolicy {
+  // skip the rest of the frame and carry on with the next one
+  Log,
+  // stop running scripts until a fixed version is hot reloaded or the app is resumed
+  Pause,
+  // pause and draw the error over the game, a click or tap on it resumes
+  Overlay,
+}
+
+impl Default for ErrorPolicy {
+  // the overlay is for development, release builds keep the game running
+  fn default() -> Self {
+    if cfg!(debug_assertions) {
+      ErrorPolicy::Overlay
+    } else {
+      ErrorPolicy::Log
+    }
+  }
+}
+
+#[derive(Debug, Clone)]
+pub struct JsError {
+  // what the runtime was calling into, e.g. "update", "timer" or "load"
+  pub source: String,
+  pub message: String,
+  pub file: Option<String>,
+  pub line: Option<i32>,
+  pub stack: Option<String>,
+}
+
+impl JsError {
+  // `error` is what the failed call returned, a pending exception is taken from `ctx`
+  pub fn new(ctx: Ctx, source: &str, error: Error) -> Self {
+    let mut js_error = Self {
+      source: source.to_string(),
+      message: String::new(),
+      file: None,
+      line: None,
+      stack: None,
+    };
+    match CaughtError::from_error(ctx, error) {
+      CaughtError::Exception(exception) => {
+        js_error.message = exception.message().unwrap_or_default();
+        js_error.file = exception.file();
+        js_error.line = exception.line();
+        js_error.stack = exception
+          .stack()
+          .map(|stack| stack.trim_end().to_string())
+          .filter(|stack| !stack.trim().is_empty());
+      }
+      // `throw "text"` and other non-Error values
+      CaughtError::Value(value) => {
+        js_error.message = match value.as_string().and_then(|text| text.to_string().ok()) {
+          Some(text) => text,
+          None => format!("uncaught {:?}", value),
+        }
+      }
+      CaughtError::Error(error) => js_error.message = error.to_string(),
+    }
+    js_error
+  }
+}
+
+// "file:line: message" plus the JS stack when there is one
+impl fmt::Display for JsError {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match (&self.file, self.line) {
+      (Some(file), Some(line)) => write!(f, "{}:{}: ", file, line)?,
+      (Some(file), None) => write!(f, "{}: ", file)?,
+      _ => (),
+    }
+    write!(f, "{}", self.message)?;
+    match self.stack {
+      Some(ref stack) => write!(f, "\n{}", stack),
+      None => Ok(()),
+    }
+  }
+}
+
+// an async callback doesn't throw, it returns a promise that rejects once its jobs ran,
+// the rejection is queued here and reported like an exception, a promise the script never
+// hands back to the runtime isn't seen, quickjs only reports those to a host tracker
+#[derive(Default, Clone)]
+pub struct Rejections(Rc<RefCell<Vec<JsError>>>);
+
+impl Rejections {
+  // `result` is what a callback returned, anything but a promise is ignored
+  pub fn watch<'js>(
+    &self,
+    ctx: Ctx<'js>,
+    source: &str,
+    result: Value<'js>,
+  ) -> rquickjs::Result<()> {
+    let promise = match result.into_object() {
+      Some(promise) => promise,
+      None => return Ok(()),
+    };
+    let then = match promise.get::<_, Function>("then") {
+      Ok(then) => then,
+      Err(_) => return Ok(()),
+    };
+    let rejections = self.clone();
+    let source = source.to_string();
+    // throwing the reason makes it the pending exception `JsError` reads
+    let rejected = move |ctx: Ctx<'js>, reason: Value<'js>| {
+      let error = ctx.throw(reason);
+      let error = JsError::new(ctx, &source, error);
+      rejections.0.borrow_mut().push(error);
+    };
+    then.call((This(promise), Undefined, Function::new(ctx, rejected)?))
+  }
+
+  pub fn take(&self) -> Vec<JsError> {
+    std::mem::take(&mut *self.0.borrow_mut())
+  }
+}
@@ -0,0 +1,545 @@
+use rquickjs::{bind, Ctx, Exception, Object, Result, Value};
+
+use crate::{
+  gdx::g2d::{
+    batcher::DrawOptions,
+    texture::{TextureFilter, TextureWrap},
+  },
+  js::gl::with_f32s,
+};
+
+// samplers are set with the gl constants, e.g. `texture.setFilter(gl.NEAREST, gl.NEAREST)`
+fn texture_filter(ctx: Ctx, value: u32) -> Result<TextureFilter> {
+  match TextureFilter::from_gl_enum(value) {
+    Some(filter) => Ok(filter),
+    None => Err(Exception::throw_message(
+      ctx,
+      &format!("{:#x} is not a texture filter", value),
+    )),
+  }
+}
+
+fn texture_wrap(ctx: Ctx, value: u32) -> Result<TextureWrap> {
+  match TextureWrap::from_gl_enum(value) {
+    Some(wrap) => Ok(wrap),
+    None => Err(Exception::throw_message(
+      ctx,
+      &format!("{:#x} is not a texture wrap mode", value),
+    )),
+  }
+}
+
+// 16 numbers, column major, e.g. `camera.getCombined()`
+fn matrix<'js>(ctx: Ctx<'js>, value: &Value<'js>, name: &str) -> Result<[f32; 16]> {
+  with_f32s(value, |values| match <[f32; 16]>::try_from(values) {
+    Ok(values) => Ok(values),
+    Err(_) => Err(Exception::throw_message(
+      ctx,
+      &format!("{} needs 16 values, got {}", name, values.len()),
+    )),
+  })?
+}
+
+// e.g. `{ originX: 10, originY: 10, rotation: 1.5 }`, what's left out keeps its default
+fn draw_options(options: Option<&Object>) -> Result<DrawOptions> {
+  let mut draw = DrawOptions::default();
+  if let Some(options) = options {
+    for (name, value) in [
+      ("originX", &mut draw.origin_x),
+      ("originY", &mut draw.origin_y),
+      ("rotation", &mut draw.rotation),
+      ("scaleX", &mut draw.scale_x),
+      ("scaleY", &mut draw.scale_y),
+      ("u1", &mut draw.u1),
+      ("v1", &mut draw.v1),
+      ("u2", &mut draw.u2),
+      ("v2", &mut draw.v2),
+    ] {
+      if let Some(option) = options.get::<_, Option<f32>>(name)? {
+        *value = option;
+      }
+    }
+    if let Some(rotate) = options.get::<_, Option<bool>>("rotate")? {
+      draw.rotate = rotate;
+    }
+  }
+  Ok(draw)
+}
+
+// the 2d helpers as JS classes, each constructor that needs GL takes the `gl` object
+// handed to `start(gl)`:
+//
+//   const batch = new PolygonBatch(gl);
+//   const camera = new OrthoCamera(width, height, width, height);
+//   const fox = new Texture(gl, "fox.png");
+//
+// state is read through getX() methods rather than property getters, rquickjs 0.3 calls
+// getters with a null argument pointer which debug builds of newer toolchains abort on
+#[bind(object, public = "crate")]
+#[quickjs(bare)]
+pub mod gdx_js {
+  use rquickjs::{class::Ref, function::Opt, Ctx, Exception, Object, Result, Value};
+  use std::{cell::RefCell, rc::Rc};
+
+  use crate::{
+    gdx::{
+      g2d::{
+        batcher::{PolygonBatch, DEFAULT_MAX_VERTICES},
+        ortho_cam::OrthoCamera,
+        texture::{LoadOptions, PixelFormat, Texture},
+        texture_atlas::TextureAtlas,
+        texture_region::TextureRegion,
+      },
+      math::vector2::Vec2,
+    },
+    js::gl::glow_js::JsContext,
+  };
+
+  use super::{draw_options, matrix, texture_filter, texture_wrap};
+
+  #[quickjs(rename = "Texture")]
+  pub struct JsTexture {
+    #[quickjs(skip)]
+    pub texture: Rc<Texture>,
+  }
+
+  #[quickjs(rename = "Texture")]
+  impl JsTexture {
+    // loads `name` from the asset root, like module names, the options are
+    // `{ premultiplyAlpha: true, format: "rgba" | "rgb" | "luminance", minFilter: gl.NEAREST,
+    // magFilter, wrapS: gl.REPEAT, wrapT, mipmaps: true }`
+    pub fn new<'js>(
+      ctx: Ctx<'js>,
+      gl: Ref<'js, JsContext>,
+      name: String,
+      options: Opt<Object<'js>>,
+    ) -> Result<Self> {
+      let path = gl.assets.join(&name);
+      if !path.is_file() {
+        return Err(Exception::throw_message(
+          ctx,
+          &format!("texture {} not found", name),
+        ));
+      }
+      let mut load_options = LoadOptions::default();
+      match options.0 {
+        Some(ref options) => {
+          load_options.premultiply_alpha = options
+            .get::<_, Option<bool>>("premultiplyAlpha")?
+            .unwrap_or(false);
+          load_options.format = match options.get::<_, Option<String>>("format")?.as_deref() {
+            None | Some("rgba") => PixelFormat::Rgba,
+            Some("rgb") => PixelFormat::Rgb,
+            Some("luminance") => PixelFormat::Luminance,
+            Some(format) => {
+              return Err(Exception::throw_message(
+                ctx,
+                &format!("unknown texture format {}", format),
+              ))
+            }
+          };
+          let texture = &mut load_options.texture;
+          match options.get::<_, Option<u32>>("minFilter")? {
+            Some(value) => texture.min_filter = texture_filter(ctx, value)?,
+            None => (),
+          }
+          match options.get::<_, Option<u32>>("magFilter")? {
+            Some(value) => texture.mag_filter = texture_filter(ctx, value)?,
+            None => (),
+          }
+          match options.get::<_, Option<u32>>("wrapS")? {
+            Some(value) => texture.wrap_s = texture_wrap(ctx, value)?,
+            None => (),
+          }
+          match options.get::<_, Option<u32>>("wrapT")? {
+            Some(value) => texture.wrap_t = texture_wrap(ctx, value)?,
+            None => (),
+          }
+          texture.mipmaps = options.get::<_, Option<bool>>("mipmaps")?.unwrap_or(false);
+        }
+        None => (),
+      }
+      let path = path.to_string_lossy().into_owned();
+      match Texture::try_load_with_options(&gl.gl, path, &load_options) {
+        Ok(texture) => Ok(Self { texture }),
+        Err(error) => Err(Exception::throw_message(ctx, &error.to_string())),
+      }
+    }
+    #[quickjs(rename = "getWidth")]
+    pub fn get_width(&self) -> u32 {
+      self.texture.width
+    }
+    #[quickjs(rename = "getHeight")]
+    pub fn get_height(&self) -> u32 {
+      self.texture.height
+    }
+    #[quickjs(rename = "setFilter")]
+    pub fn set_filter<'js>(&self, ctx: Ctx<'js>, min_filter: u32, mag_filter: u32) -> Result<()> {
+      let min_filter = texture_filter(ctx, min_filter)?;
+      let mag_filter = texture_filter(ctx, mag_filter)?;
+      self.texture.set_filter(min_filter, mag_filter);
+      Ok(())
+    }
+    #[quickjs(rename = "setWrap")]
+    pub fn set_wrap<'js>(&self, ctx: Ctx<'js>, wrap_s: u32, wrap_t: u32) -> Result<()> {
+      let wrap_s = texture_wrap(ctx, wrap_s)?;
+      let wrap_t = texture_wrap(ctx, wrap_t)?;
+      self.texture.set_wrap(wrap_s, wrap_t);
+      Ok(())
+    }
+    #[quickjs(rename = "generateMipmaps")]
+    pub fn generate_mipmaps(&self) {
+      self.texture.generate_mipmaps();
+    }
+    // the GL texture is also deleted once the last batch and script object drop it
+    pub fn dispose(&self) {
+      self.texture.dispose();
+    }
+  }
+
+  #[quickjs(rename = "TextureRegion")]
+  pub struct JsTextureRegion {
+    #[quickjs(skip)]
+    pub region: RefCell<TextureRegion>,
+  }
+
+  #[quickjs(rename = "TextureRegion")]
+  impl JsTextureRegion {
+    // `new TextureRegion(texture)` covers the whole texture, `new TextureRegion(texture, x, y,
+    // width, height)` a rectangle in pixels from the top left
+    pub fn new<'js>(
+      texture: Ref<'js, JsTexture>,
+      x: Opt<u32>,
+      y: Opt<u32>,
+      width: Opt<u32>,
+      height: Opt<u32>,
+    ) -> Self {
+      let texture = &texture.texture;
+      let x = x.0.unwrap_or(0);
+      let y = y.0.unwrap_or(0);
+      let width = width.0.unwrap_or(texture.width.saturating_sub(x));
+      let height = height.0.unwrap_or(texture.height.saturating_sub(y));
+      Self {
+        region: RefCell::new(TextureRegion::from_rect(texture, x, y, width, height)),
+      }
+    }
+    #[quickjs(skip)]
+    pub fn wrap(region: TextureRegion) -> Self {
+      Self {
+        region: RefCell::new(region),
+      }
+    }
+    pub fn flip(&self, x: bool, y: bool) {
+      self.region.borrow_mut().flip(x, y);
+    }
+    #[quickjs(rename = "isFlipX")]
+    pub fn is_flip_x(&self) -> bool {
+      self.region.borrow().is_flip_x()
+    }
+    #[quickjs(rename = "isFlipY")]
+    pub fn is_flip_y(&self) -> bool {
+      self.region.borrow().is_flip_y()
+    }
+    #[quickjs(rename = "setRotated")]
+    pub fn set_rotated(&self, rotated: bool) {
+      self.region.borrow_mut().rotated = rotated;
+    }
+    #[quickjs(rename = "getWidth")]
+    pub fn get_width(&self) -> u32 {
+      self.region.borrow().get_width()
+    }
+    #[quickjs(rename = "getHeight")]
+    pub fn get_height(&self) -> u32 {
+      self.region.borrow().get_height()
+    }
+    // rows of frames from the top left, `region.split(32, 32)[row][column]`
+    pub fn split(&self, tile_width: u32, tile_height: u32) -> Vec<Vec<JsTextureRegion>> {
+      let rows = self.region.borrow().split(tile_width, tile_height);
+      rows
+        .into_iter()
+        .map(|row| row.into_iter().map(JsTextureRegion::wrap).collect())
+        .collect()
+    }
+  }
+
+  #[quickjs(rename = "TextureAtlas")]
+  pub struct JsTextureAtlas {
+    #[quickjs(skip)]
+    pub atlas: TextureAtlas,
+  }
+
+  // regions come out as the packed pixels, without the whitespace the packer stripped
+  #[quickjs(rename = "TextureAtlas")]
+  impl JsTextureAtlas {
+    // a libGDX `.atlas` file from the asset root, its page images are next to it
+    pub fn new<'js>(ctx: Ctx<'js>, gl: Ref<'js, JsContext>, name: String) -> Result<Self> {
+      let path = gl.assets.join(&name).to_string_lossy().into_owned();
+      match TextureAtlas::try_load(&gl.gl, path) {
+        Ok(atlas) => Ok(Self { atlas }),
+        Err(error) => Err(Exception::throw_message(ctx, &error.to_string())),
+      }
+    }
+    // undefined if there is no such region
+    #[quickjs(rename = "findRegion")]
+    pub fn find_region(&self, name: String, index: Opt<i32>) -> Option<JsTextureRegion> {
+      let region = match index.0 {
+        Some(index) => self.atlas.find_region_with_index(&name, index),
+        None => self.atlas.find_region(&name),
+      };
+      region.map(|region| JsTextureRegion::wrap(region.region.clone()))
+    }
+    #[quickjs(rename = "findRegions")]
+    pub fn find_regions(&self, name: String) -> Vec<JsTextureRegion> {
+      self
+        .atlas
+        .find_regions(&name)
+        .into_iter()
+        .map(|region| JsTextureRegion::wrap(region.region.clone()))
+        .collect()
+    }
+    pub fn dispose(&self) {
+      self.atlas.dispose();
+    }
+  }
+
+  #[quickjs(rename = "PolygonBatch")]
+  pub struct JsPolygonBatch {
+    #[quickjs(skip)]
+    pub batch: RefCell<PolygonBatch>,
+  }
+
+  #[quickjs(rename = "PolygonBatch")]
+  impl JsPolygonBatch {
+    // `maxTextures` above 1 draws that many textures without a flush between them
+    pub fn new<'js>(
+      ctx: Ctx<'js>,
+      gl: Ref<'js, JsContext>,
+      max_vertices: Opt<usize>,
+      max_textures: Opt<usize>,
+    ) -> Result<Self> {
+      let max_vertices = max_vertices.0.unwrap_or(DEFAULT_MAX_VERTICES);
+      let max_textures = max_textures.0.unwrap_or(1);
+      match PolygonBatch::try_new_with_textures(&gl.gl, max_vertices, max_textures) {
+        Ok(batch) => Ok(Self {
+          batch: RefCell::new(batch),
+        }),
+        Err(error) => Err(Exception::throw_message(ctx, &error.to_string())),
+      }
+    }
+    // begin/end out of order throw instead of panicking like the rust side does
+    pub fn begin<'js>(&self, ctx: Ctx<'js>) -> Result<()> {
+      let mut batch = self.batch.borrow_mut();
+      if batch.is_drawing {
+        return Err(Exception::throw_message(
+          ctx,
+          "PolygonBatch is already drawing",
+        ));
+      }
+      batch.begin();
+      Ok(())
+    }
+    pub fn end<'js>(&self, ctx: Ctx<'js>) -> Result<()> {
+      let mut batch = self.batch.borrow_mut();
+      if !batch.is_drawing {
+        return Err(Exception::throw_message(ctx, "PolygonBatch is not drawing"));
+      }
+      batch.end();
+      Ok(())
+    }
+    pub fn flush(&self) {
+      self.batch.borrow_mut().flush();
+    }
+    pub fn draw<'js>(&self, texture: Ref<'js, JsTexture>, x: f32, y: f32, width: f32, height: f32) {
+      self
+        .batch
+        .borrow_mut()
+        .draw(&texture.texture, x, y, width, height);
+    }
+    // the optional part goes in an object, e.g. `{ originX: 10, originY: 10, rotation: 1.5 }`,
+    // u1/v1/u2/v2 default to the whole texture
+    #[quickjs(rename = "drawWithOptions")]
+    pub fn draw_with_options<'js>(
+      &self,
+      texture: Ref<'js, JsTexture>,
+      x: f32,
+      y: f32,
+      width: f32,
+      height: f32,
+      options: Opt<Object<'js>>,
+    ) -> Result<()> {
+      let options = draw_options(options.0.as_ref())?;
+      self
+        .batch
+        .borrow_mut()
+        .draw_with_options(&texture.texture, x, y, width, height, &options);
+      Ok(())
+    }
+    // at the region's size unless `width` and `height` are given, the options are those of
+    // `drawWithOptions` without the UVs
+    #[quickjs(rename = "drawRegion")]
+    pub fn draw_region<'js>(
+      &self,
+      region: Ref<'js, JsTextureRegion>,
+      x: f32,
+      y: f32,
+      width: Opt<f32>,
+      height: Opt<f32>,
+      options: Opt<Object<'js>>,
+    ) -> Result<()> {
+      let options = draw_options(options.0.as_ref())?;
+      let region = region.region.borrow();
+      self.batch.borrow_mut().draw_region_with_options(
+        &region,
+        x,
+        y,
+        width.0.unwrap_or(region.get_width() as f32),
+        height.0.unwrap_or(region.get_height() as f32),
+        &options,
+      );
+      Ok(())
+    }
+    #[quickjs(rename = "setColor")]
+    pub fn set_color(&self, r: f32, g: f32, b: f32, a: f32) {
+      self.batch.borrow_mut().set_color_rgba(r, g, b, a);
+    }
+    #[quickjs(rename = "setYDown")]
+    pub fn set_y_down(&self, y_down: bool) {
+      self.batch.borrow_mut().set_y_down(y_down);
+    }
+    #[quickjs(rename = "setBlendFunction")]
+    pub fn set_blend_function(&self, src: u32, dst: u32) {
+      self.batch.borrow_mut().set_blend_function(src, dst);
+    }
+    #[quickjs(rename = "setBlendFunctionSeparate")]
+    pub fn set_blend_function_separate(
+      &self,
+      src_color: u32,
+      dst_color: u32,
+      src_alpha: u32,
+      dst_alpha: u32,
+    ) {
+      self
+        .batch
+        .borrow_mut()
+        .set_blend_function_separate(src_color, dst_color, src_alpha, dst_alpha);
+    }
+    // takes `camera.getCombined()` or any other 16 numbers, column major
+    #[quickjs(rename = "setProjection")]
+    pub fn set_projection<'js>(&self, ctx: Ctx<'js>, projection: Value<'js>) -> Result<()> {
+      let projection = matrix(ctx, &projection, "projection")?;
+      self.batch.borrow_mut().set_projection(&projection);
+      Ok(())
+    }
+    // applied before the projection to everything drawn after it
+    #[quickjs(rename = "setTransform")]
+    pub fn set_transform<'js>(&self, ctx: Ctx<'js>, transform: Value<'js>) -> Result<()> {
+      let transform = matrix(ctx, &transform, "transform")?;
+      self.batch.borrow_mut().set_transform(&transform);
+      Ok(())
+    }
+    #[quickjs(rename = "getTransform")]
+    pub fn get_transform(&self) -> Vec<f32> {
+      self.batch.borrow().get_transform().to_vec()
+    }
+    // combined with the current transform until the matching `popTransform()`
+    #[quickjs(rename = "pushTransform")]
+    pub fn push_transform<'js>(&self, ctx: Ctx<'js>, transform: Value<'js>) -> Result<()> {
+      let transform = matrix(ctx, &transform, "transform")?;
+      self.batch.borrow_mut().push_transform(&transform);
+      Ok(())
+    }
+    #[quickjs(rename = "popTransform")]
+    pub fn pop_transform(&self, ctx: Ctx) -> Result<()> {
+      let mut batch = self.batch.borrow_mut();
+      match batch.get_transform_depth() {
+        0 => Err(Exception::throw_message(
+          ctx,
+          "popTransform without a pushTransform",
+        )),
+        _ => {
+          batch.pop_transform();
+          Ok(())
+        }
+      }
+    }
+    #[quickjs(rename = "getDrawCalls")]
+    pub fn get_draw_calls(&self) -> i32 {
+      self.batch.borrow().get_draw_calls()
+    }
+    #[quickjs(rename = "getMaxTextures")]
+    pub fn get_max_textures(&self) -> usize {
+      self.batch.borrow().get_max_textures()
+    }
+    pub fn dispose(&self) {
+      self.batch.borrow_mut().dispose();
+    }
+  }
+
+  #[quickjs(rename = "OrthoCamera")]
+  pub struct JsOrthoCamera {
+    #[quickjs(skip)]
+    pub camera: RefCell<OrthoCamera>,
+  }
+
+  #[quickjs(rename = "OrthoCamera")]
+  impl JsOrthoCamera {
+    pub fn new(
+      viewport_width: f32,
+      viewport_height: f32,
+      screen_width: f32,
+      screen_height: f32,
+    ) -> Self {
+      Self {
+        camera: RefCell::new(OrthoCamera::new(
+          viewport_width,
+          viewport_height,
+          screen_width,
+          screen_height,
+        )),
+      }
+    }
+    #[quickjs(rename = "setYDown")]
+    pub fn set_y_down(&self, y_down: bool) {
+      self.camera.borrow_mut().set_y_down(y_down);
+    }
+    #[quickjs(rename = "setPosition")]
+    pub fn set_position(&self, x: f32, y: f32) {
+      self.camera.borrow_mut().set_position(x, y);
+    }
+    #[quickjs(rename = "setViewport")]
+    pub fn set_viewport(&self, viewport_width: f32, viewport_height: f32) {
+      self
+        .camera
+        .borrow_mut()
+        .set_viewport(viewport_width, viewport_height);
+    }
+    pub fn resize(
+      &self,
+      viewport_width: f32,
+      viewport_height: f32,
+      screen_width: f32,
+      screen_height: f32,
+    ) {
+      self
+        .camera
+        .borrow_mut()
+        .resize(viewport_width, viewport_height, screen_width, screen_height);
+    }
+    pub fn update(&self) {
+      self.camera.borrow_mut().update();
+    }
+    // screen position (e.g. from input.getPointerX/Y) to world position, as `[x, y]`
+    pub fn unproject(&self, x: f32, y: f32) -> Vec<f32> {
+      let mut world = Vec2::zero();
+      self
+        .camera
+        .borrow()
+        .unproject_vec2(&mut world, &Vec2::new(x, y));
+      vec![world.x, world.y]
+    }
+    #[quickjs(rename = "getCombined")]
+    pub fn get_combined(&self) -> Vec<f32> {
+      self.camera.borrow().combined.to_vec()
+    }
+  }
+}
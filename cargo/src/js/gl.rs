@@ -0,0 +1,1583 @@
+use rquickjs::{bind, ArrayBuffer, Error, Result, TypedArray, Value};
+use std::{collections::HashMap, num::NonZeroU32};
+
+// GL objects cross into JS as their raw ids, `null` (or 0) unbinds, like WebGL's null objects
+fn handle<T>(id: Option<u32>, wrap: fn(NonZeroU32) -> T) -> Option<T> {
+  id.and_then(NonZeroU32::new).map(wrap)
+}
+
+// WebGL takes an ArrayBuffer or any view on one (typed arrays, DataView) where it wants raw data
+fn with_bytes<R>(value: &Value, f: impl FnOnce(&[u8]) -> R) -> Result<R> {
+  let object = match value.as_object() {
+    Some(object) => object,
+    None => return Err(Error::new_from_js(value.type_name(), "ArrayBufferView")),
+  };
+  if let Ok(buffer) = ArrayBuffer::from_object(object.clone()) {
+    return Ok(f(buffer.as_bytes().unwrap_or(&[])));
+  }
+  let buffer: ArrayBuffer = object.get("buffer")?;
+  let offset: usize = object.get("byteOffset")?;
+  let length: usize = object.get("byteLength")?;
+  match buffer
+    .as_bytes()
+    .and_then(|bytes| bytes.get(offset..offset + length))
+  {
+    Some(bytes) => Ok(f(bytes)),
+    None => Ok(f(&[])),
+  }
+}
+
+// uniforms and vertex attributes take a Float32Array or a plain array of numbers
+pub(crate) fn with_f32s<R>(value: &Value, f: impl FnOnce(&[f32]) -> R) -> Result<R> {
+  if let Ok(array) = TypedArray::<f32>::from_value(value.clone()) {
+    return Ok(f(array.as_ref()));
+  }
+  let values: Vec<f32> = value.get()?;
+  Ok(f(&values))
+}
+
+fn with_i32s<R>(value: &Value, f: impl FnOnce(&[i32]) -> R) -> Result<R> {
+  if let Ok(array) = TypedArray::<i32>::from_value(value.clone()) {
+    return Ok(f(array.as_ref()));
+  }
+  let values: Vec<i32> = value.get()?;
+  Ok(f(&values))
+}
+
+// GL reads height rows from the pointer it gets, each but the last padded to `alignment`,
+// so short arrays have to be rejected here like WebGL does
+fn check_pixels(
+  bytes: &[u8],
+  width: i32,
+  height: i32,
+  format: u32,
+  data_type: u32,
+  alignment: usize,
+) -> Result<()> {
+  let channels = match format {
+    glow::ALPHA | glow::LUMINANCE | glow::DEPTH_COMPONENT => 1,
+    glow::LUMINANCE_ALPHA => 2,
+    glow::RGB => 3,
+    glow::RGBA => 4,
+    _ => return Ok(()),
+  };
+  let pixel_size = match data_type {
+    glow::UNSIGNED_BYTE => channels,
+    glow::UNSIGNED_SHORT_5_6_5 | glow::UNSIGNED_SHORT_4_4_4_4 | glow::UNSIGNED_SHORT_5_5_5_1 => 2,
+    glow::UNSIGNED_SHORT => channels * 2,
+    glow::FLOAT | glow::UNSIGNED_INT => channels * 4,
+    _ => return Ok(()),
+  };
+  let (width, height) = (width.max(0) as usize, height.max(0) as usize);
+  let row = width * pixel_size;
+  let needed = match height {
+    0 => 0,
+    _ => (height - 1) * row.next_multiple_of(alignment.max(1)) + row,
+  };
+  if bytes.len() < needed {
+    return Err(Error::new_from_js_message(
+      "ArrayBufferView",
+      "pixels",
+      format!("{} bytes given, {} needed", bytes.len(), needed),
+    ));
+  }
+  Ok(())
+}
+
+// what the JS side set up for one attribute of one vertex array, 0 is the default one
+#[derive(Debug, Clone, Copy, Default)]
+struct AttributeState {
+  enabled: bool,
+  buffer: Option<u32>,
+}
+
+// an enabled attribute without a buffer would make GL read from client memory at the
+// offset it was given, returns the first one of `vertex_array`
+fn unbacked_attribute(
+  attributes: &HashMap<(u32, u32), AttributeState>,
+  vertex_array: u32,
+) -> Option<u32> {
+  attributes
+    .iter()
+    .filter(|((array, _), state)| *array == vertex_array && state.enabled && state.buffer.is_none())
+    .map(|((_, index), _)| *index)
+    .min()
+}
+
+fn non_null<'a, 'js>(value: &'a Option<Value<'js>>) -> Option<&'a Value<'js>> {
+  value
+    .as_ref()
+    .filter(|value| !value.is_null() && !value.is_undefined())
+}
+
+// a WebGL1 shaped `gl` object, method names and constants follow WebGLRenderingContext
+// so existing snippets run unchanged, vertex arrays come from WebGL2 since desktop
+// core profiles can't draw without one
+#[bind(object, public = "crate")]
+#[quickjs(bare)]
+pub mod glow_js {
+  use glow::*;
+  use rquickjs::{function::Opt, Ctx, Error, Exception, Result, Value};
+  use std::{cell::RefCell, collections::HashMap, path::PathBuf, rc::Rc};
+
+  use super::{
+    check_pixels, handle, non_null, unbacked_attribute, with_bytes, with_f32s, with_i32s,
+    AttributeState,
+  };
+
+  pub struct JsContext {
+    #[quickjs(skip)]
+    pub gl: Rc<Context>,
+    // the classes in `gdx_js` load files by name through this, e.g. `new Texture(gl, "fox.png")`
+    #[quickjs(skip)]
+    pub assets: PathBuf,
+    // keyed by vertex array and attribute index, bindings are read back from GL instead since
+    // the batches and textures on the Rust side change them too
+    #[quickjs(skip)]
+    attributes: RefCell<HashMap<(u32, u32), AttributeState>>,
+  }
+
+  impl JsContext {
+    #[quickjs(proto)]
+    pub const DEPTH_BUFFER_BIT: u32 = glow::DEPTH_BUFFER_BIT;
+    #[quickjs(proto)]
+    pub const STENCIL_BUFFER_BIT: u32 = glow::STENCIL_BUFFER_BIT;
+    #[quickjs(proto)]
+    pub const COLOR_BUFFER_BIT: u32 = glow::COLOR_BUFFER_BIT;
+    #[quickjs(proto)]
+    pub const POINTS: u32 = glow::POINTS;
+    #[quickjs(proto)]
+    pub const LINES: u32 = glow::LINES;
+    #[quickjs(proto)]
+    pub const LINE_LOOP: u32 = glow::LINE_LOOP;
+    #[quickjs(proto)]
+    pub const LINE_STRIP: u32 = glow::LINE_STRIP;
+    #[quickjs(proto)]
+    pub const TRIANGLES: u32 = glow::TRIANGLES;
+    #[quickjs(proto)]
+    pub const TRIANGLE_STRIP: u32 = glow::TRIANGLE_STRIP;
+    #[quickjs(proto)]
+    pub const TRIANGLE_FAN: u32 = glow::TRIANGLE_FAN;
+    #[quickjs(proto)]
+    pub const ZERO: u32 = glow::ZERO;
+    #[quickjs(proto)]
+    pub const ONE: u32 = glow::ONE;
+    #[quickjs(proto)]
+    pub const SRC_COLOR: u32 = glow::SRC_COLOR;
+    #[quickjs(proto)]
+    pub const ONE_MINUS_SRC_COLOR: u32 = glow::ONE_MINUS_SRC_COLOR;
+    #[quickjs(proto)]
+    pub const SRC_ALPHA: u32 = glow::SRC_ALPHA;
+    #[quickjs(proto)]
+    pub const ONE_MINUS_SRC_ALPHA: u32 = glow::ONE_MINUS_SRC_ALPHA;
+    #[quickjs(proto)]
+    pub const DST_ALPHA: u32 = glow::DST_ALPHA;
+    #[quickjs(proto)]
+    pub const ONE_MINUS_DST_ALPHA: u32 = glow::ONE_MINUS_DST_ALPHA;
+    #[quickjs(proto)]
+    pub const DST_COLOR: u32 = glow::DST_COLOR;
+    #[quickjs(proto)]
+    pub const ONE_MINUS_DST_COLOR: u32 = glow::ONE_MINUS_DST_COLOR;
+    #[quickjs(proto)]
+    pub const SRC_ALPHA_SATURATE: u32 = glow::SRC_ALPHA_SATURATE;
+    #[quickjs(proto)]
+    pub const FUNC_ADD: u32 = glow::FUNC_ADD;
+    #[quickjs(proto)]
+    pub const BLEND_EQUATION: u32 = glow::BLEND_EQUATION;
+    #[quickjs(proto)]
+    pub const BLEND_EQUATION_RGB: u32 = glow::BLEND_EQUATION_RGB;
+    #[quickjs(proto)]
+    pub const BLEND_EQUATION_ALPHA: u32 = glow::BLEND_EQUATION_ALPHA;
+    #[quickjs(proto)]
+    pub const FUNC_SUBTRACT: u32 = glow::FUNC_SUBTRACT;
+    #[quickjs(proto)]
+    pub const FUNC_REVERSE_SUBTRACT: u32 = glow::FUNC_REVERSE_SUBTRACT;
+    #[quickjs(proto)]
+    pub const BLEND_DST_RGB: u32 = glow::BLEND_DST_RGB;
+    #[quickjs(proto)]
+    pub const BLEND_SRC_RGB: u32 = glow::BLEND_SRC_RGB;
+    #[quickjs(proto)]
+    pub const BLEND_DST_ALPHA: u32 = glow::BLEND_DST_ALPHA;
+    #[quickjs(proto)]
+    pub const BLEND_SRC_ALPHA: u32 = glow::BLEND_SRC_ALPHA;
+    #[quickjs(proto)]
+    pub const CONSTANT_COLOR: u32 = glow::CONSTANT_COLOR;
+    #[quickjs(proto)]
+    pub const ONE_MINUS_CONSTANT_COLOR: u32 = glow::ONE_MINUS_CONSTANT_COLOR;
+    #[quickjs(proto)]
+    pub const CONSTANT_ALPHA: u32 = glow::CONSTANT_ALPHA;
+    #[quickjs(proto)]
+    pub const ONE_MINUS_CONSTANT_ALPHA: u32 = glow::ONE_MINUS_CONSTANT_ALPHA;
+    #[quickjs(proto)]
+    pub const BLEND_COLOR: u32 = glow::BLEND_COLOR;
+    #[quickjs(proto)]
+    pub const ARRAY_BUFFER: u32 = glow::ARRAY_BUFFER;
+    #[quickjs(proto)]
+    pub const ELEMENT_ARRAY_BUFFER: u32 = glow::ELEMENT_ARRAY_BUFFER;
+    #[quickjs(proto)]
+    pub const ARRAY_BUFFER_BINDING: u32 = glow::ARRAY_BUFFER_BINDING;
+    #[quickjs(proto)]
+    pub const ELEMENT_ARRAY_BUFFER_BINDING: u32 = glow::ELEMENT_ARRAY_BUFFER_BINDING;
+    #[quickjs(proto)]
+    pub const STREAM_DRAW: u32 = glow::STREAM_DRAW;
+    #[quickjs(proto)]
+    pub const STATIC_DRAW: u32 = glow::STATIC_DRAW;
+    #[quickjs(proto)]
+    pub const DYNAMIC_DRAW: u32 = glow::DYNAMIC_DRAW;
+    #[quickjs(proto)]
+    pub const BUFFER_SIZE: u32 = glow::BUFFER_SIZE;
+    #[quickjs(proto)]
+    pub const BUFFER_USAGE: u32 = glow::BUFFER_USAGE;
+    #[quickjs(proto)]
+    pub const CURRENT_VERTEX_ATTRIB: u32 = glow::CURRENT_VERTEX_ATTRIB;
+    #[quickjs(proto)]
+    pub const FRONT: u32 = glow::FRONT;
+    #[quickjs(proto)]
+    pub const BACK: u32 = glow::BACK;
+    #[quickjs(proto)]
+    pub const FRONT_AND_BACK: u32 = glow::FRONT_AND_BACK;
+    #[quickjs(proto)]
+    pub const CULL_FACE: u32 = glow::CULL_FACE;
+    #[quickjs(proto)]
+    pub const BLEND: u32 = glow::BLEND;
+    #[quickjs(proto)]
+    pub const DITHER: u32 = glow::DITHER;
+    #[quickjs(proto)]
+    pub const STENCIL_TEST: u32 = glow::STENCIL_TEST;
+    #[quickjs(proto)]
+    pub const DEPTH_TEST: u32 = glow::DEPTH_TEST;
+    #[quickjs(proto)]
+    pub const SCISSOR_TEST: u32 = glow::SCISSOR_TEST;
+    #[quickjs(proto)]
+    pub const POLYGON_OFFSET_FILL: u32 = glow::POLYGON_OFFSET_FILL;
+    #[quickjs(proto)]
+    pub const SAMPLE_ALPHA_TO_COVERAGE: u32 = glow::SAMPLE_ALPHA_TO_COVERAGE;
+    #[quickjs(proto)]
+    pub const SAMPLE_COVERAGE: u32 = glow::SAMPLE_COVERAGE;
+    #[quickjs(proto)]
+    pub const NO_ERROR: u32 = glow::NO_ERROR;
+    #[quickjs(proto)]
+    pub const INVALID_ENUM: u32 = glow::INVALID_ENUM;
+    #[quickjs(proto)]
+    pub const INVALID_VALUE: u32 = glow::INVALID_VALUE;
+    #[quickjs(proto)]
+    pub const INVALID_OPERATION: u32 = glow::INVALID_OPERATION;
+    #[quickjs(proto)]
+    pub const OUT_OF_MEMORY: u32 = glow::OUT_OF_MEMORY;
+    #[quickjs(proto)]
+    pub const CW: u32 = glow::CW;
+    #[quickjs(proto)]
+    pub const CCW: u32 = glow::CCW;
+    #[quickjs(proto)]
+    pub const LINE_WIDTH: u32 = glow::LINE_WIDTH;
+    #[quickjs(proto)]
+    pub const ALIASED_LINE_WIDTH_RANGE: u32 = glow::ALIASED_LINE_WIDTH_RANGE;
+    #[quickjs(proto)]
+    pub const CULL_FACE_MODE: u32 = glow::CULL_FACE_MODE;
+    #[quickjs(proto)]
+    pub const FRONT_FACE: u32 = glow::FRONT_FACE;
+    #[quickjs(proto)]
+    pub const DEPTH_RANGE: u32 = glow::DEPTH_RANGE;
+    #[quickjs(proto)]
+    pub const DEPTH_WRITEMASK: u32 = glow::DEPTH_WRITEMASK;
+    #[quickjs(proto)]
+    pub const DEPTH_CLEAR_VALUE: u32 = glow::DEPTH_CLEAR_VALUE;
+    #[quickjs(proto)]
+    pub const DEPTH_FUNC: u32 = glow::DEPTH_FUNC;
+    #[quickjs(proto)]
+    pub const STENCIL_CLEAR_VALUE: u32 = glow::STENCIL_CLEAR_VALUE;
+    #[quickjs(proto)]
+    pub const STENCIL_FUNC: u32 = glow::STENCIL_FUNC;
+    #[quickjs(proto)]
+    pub const STENCIL_FAIL: u32 = glow::STENCIL_FAIL;
+    #[quickjs(proto)]
+    pub const STENCIL_PASS_DEPTH_FAIL: u32 = glow::STENCIL_PASS_DEPTH_FAIL;
+    #[quickjs(proto)]
+    pub const STENCIL_PASS_DEPTH_PASS: u32 = glow::STENCIL_PASS_DEPTH_PASS;
+    #[quickjs(proto)]
+    pub const STENCIL_REF: u32 = glow::STENCIL_REF;
+    #[quickjs(proto)]
+    pub const STENCIL_VALUE_MASK: u32 = glow::STENCIL_VALUE_MASK;
+    #[quickjs(proto)]
+    pub const STENCIL_WRITEMASK: u32 = glow::STENCIL_WRITEMASK;
+    #[quickjs(proto)]
+    pub const STENCIL_BACK_FUNC: u32 = glow::STENCIL_BACK_FUNC;
+    #[quickjs(proto)]
+    pub const STENCIL_BACK_FAIL: u32 = glow::STENCIL_BACK_FAIL;
+    #[quickjs(proto)]
+    pub const STENCIL_BACK_PASS_DEPTH_FAIL: u32 = glow::STENCIL_BACK_PASS_DEPTH_FAIL;
+    #[quickjs(proto)]
+    pub const STENCIL_BACK_PASS_DEPTH_PASS: u32 = glow::STENCIL_BACK_PASS_DEPTH_PASS;
+    #[quickjs(proto)]
+    pub const STENCIL_BACK_REF: u32 = glow::STENCIL_BACK_REF;
+    #[quickjs(proto)]
+    pub const STENCIL_BACK_VALUE_MASK: u32 = glow::STENCIL_BACK_VALUE_MASK;
+    #[quickjs(proto)]
+    pub const STENCIL_BACK_WRITEMASK: u32 = glow::STENCIL_BACK_WRITEMASK;
+    #[quickjs(proto)]
+    pub const VIEWPORT: u32 = glow::VIEWPORT;
+    #[quickjs(proto)]
+    pub const SCISSOR_BOX: u32 = glow::SCISSOR_BOX;
+    #[quickjs(proto)]
+    pub const COLOR_CLEAR_VALUE: u32 = glow::COLOR_CLEAR_VALUE;
+    #[quickjs(proto)]
+    pub const COLOR_WRITEMASK: u32 = glow::COLOR_WRITEMASK;
+    #[quickjs(proto)]
+    pub const UNPACK_ALIGNMENT: u32 = glow::UNPACK_ALIGNMENT;
+    #[quickjs(proto)]
+    pub const PACK_ALIGNMENT: u32 = glow::PACK_ALIGNMENT;
+    #[quickjs(proto)]
+    pub const MAX_TEXTURE_SIZE: u32 = glow::MAX_TEXTURE_SIZE;
+    #[quickjs(proto)]
+    pub const MAX_VIEWPORT_DIMS: u32 = glow::MAX_VIEWPORT_DIMS;
+    #[quickjs(proto)]
+    pub const SUBPIXEL_BITS: u32 = glow::SUBPIXEL_BITS;
+    #[quickjs(proto)]
+    pub const POLYGON_OFFSET_UNITS: u32 = glow::POLYGON_OFFSET_UNITS;
+    #[quickjs(proto)]
+    pub const POLYGON_OFFSET_FACTOR: u32 = glow::POLYGON_OFFSET_FACTOR;
+    #[quickjs(proto)]
+    pub const TEXTURE_BINDING_2D: u32 = glow::TEXTURE_BINDING_2D;
+    #[quickjs(proto)]
+    pub const SAMPLE_BUFFERS: u32 = glow::SAMPLE_BUFFERS;
+    #[quickjs(proto)]
+    pub const SAMPLES: u32 = glow::SAMPLES;
+    #[quickjs(proto)]
+    pub const SAMPLE_COVERAGE_VALUE: u32 = glow::SAMPLE_COVERAGE_VALUE;
+    #[quickjs(proto)]
+    pub const SAMPLE_COVERAGE_INVERT: u32 = glow::SAMPLE_COVERAGE_INVERT;
+    #[quickjs(proto)]
+    pub const DONT_CARE: u32 = glow::DONT_CARE;
+    #[quickjs(proto)]
+    pub const FASTEST: u32 = glow::FASTEST;
+    #[quickjs(proto)]
+    pub const NICEST: u32 = glow::NICEST;
+    #[quickjs(proto)]
+    pub const BYTE: u32 = glow::BYTE;
+    #[quickjs(proto)]
+    pub const UNSIGNED_BYTE: u32 = glow::UNSIGNED_BYTE;
+    #[quickjs(proto)]
+    pub const SHORT: u32 = glow::SHORT;
+    #[quickjs(proto)]
+    pub const UNSIGNED_SHORT: u32 = glow::UNSIGNED_SHORT;
+    #[quickjs(proto)]
+    pub const INT: u32 = glow::INT;
+    #[quickjs(proto)]
+    pub const UNSIGNED_INT: u32 = glow::UNSIGNED_INT;
+    #[quickjs(proto)]
+    pub const FLOAT: u32 = glow::FLOAT;
+    #[quickjs(proto)]
+    pub const DEPTH_COMPONENT: u32 = glow::DEPTH_COMPONENT;
+    #[quickjs(proto)]
+    pub const ALPHA: u32 = glow::ALPHA;
+    #[quickjs(proto)]
+    pub const RGB: u32 = glow::RGB;
+    #[quickjs(proto)]
+    pub const RGBA: u32 = glow::RGBA;
+    #[quickjs(proto)]
+    pub const LUMINANCE: u32 = glow::LUMINANCE;
+    #[quickjs(proto)]
+    pub const LUMINANCE_ALPHA: u32 = glow::LUMINANCE_ALPHA;
+    #[quickjs(proto)]
+    pub const UNSIGNED_SHORT_4_4_4_4: u32 = glow::UNSIGNED_SHORT_4_4_4_4;
+    #[quickjs(proto)]
+    pub const UNSIGNED_SHORT_5_5_5_1: u32 = glow::UNSIGNED_SHORT_5_5_5_1;
+    #[quickjs(proto)]
+    pub const UNSIGNED_SHORT_5_6_5: u32 = glow::UNSIGNED_SHORT_5_6_5;
+    #[quickjs(proto)]
+    pub const FRAGMENT_SHADER: u32 = glow::FRAGMENT_SHADER;
+    #[quickjs(proto)]
+    pub const VERTEX_SHADER: u32 = glow::VERTEX_SHADER;
+    #[quickjs(proto)]
+    pub const MAX_VERTEX_ATTRIBS: u32 = glow::MAX_VERTEX_ATTRIBS;
+    #[quickjs(proto)]
+    pub const MAX_VERTEX_UNIFORM_VECTORS: u32 = glow::MAX_VERTEX_UNIFORM_VECTORS;
+    #[quickjs(proto)]
+    pub const MAX_VARYING_VECTORS: u32 = glow::MAX_VARYING_VECTORS;
+    #[quickjs(proto)]
+    pub const MAX_COMBINED_TEXTURE_IMAGE_UNITS: u32 = glow::MAX_COMBINED_TEXTURE_IMAGE_UNITS;
+    #[quickjs(proto)]
+    pub const MAX_VERTEX_TEXTURE_IMAGE_UNITS: u32 = glow::MAX_VERTEX_TEXTURE_IMAGE_UNITS;
+    #[quickjs(proto)]
+    pub const MAX_TEXTURE_IMAGE_UNITS: u32 = glow::MAX_TEXTURE_IMAGE_UNITS;
+    #[quickjs(proto)]
+    pub const MAX_FRAGMENT_UNIFORM_VECTORS: u32 = glow::MAX_FRAGMENT_UNIFORM_VECTORS;
+    #[quickjs(proto)]
+    pub const SHADER_TYPE: u32 = glow::SHADER_TYPE;
+    #[quickjs(proto)]
+    pub const DELETE_STATUS: u32 = glow::DELETE_STATUS;
+    #[quickjs(proto)]
+    pub const LINK_STATUS: u32 = glow::LINK_STATUS;
+    #[quickjs(proto)]
+    pub const VALIDATE_STATUS: u32 = glow::VALIDATE_STATUS;
+    #[quickjs(proto)]
+    pub const ATTACHED_SHADERS: u32 = glow::ATTACHED_SHADERS;
+    #[quickjs(proto)]
+    pub const ACTIVE_UNIFORMS: u32 = glow::ACTIVE_UNIFORMS;
+    #[quickjs(proto)]
+    pub const ACTIVE_ATTRIBUTES: u32 = glow::ACTIVE_ATTRIBUTES;
+    #[quickjs(proto)]
+    pub const SHADING_LANGUAGE_VERSION: u32 = glow::SHADING_LANGUAGE_VERSION;
+    #[quickjs(proto)]
+    pub const CURRENT_PROGRAM: u32 = glow::CURRENT_PROGRAM;
+    #[quickjs(proto)]
+    pub const NEVER: u32 = glow::NEVER;
+    #[quickjs(proto)]
+    pub const LESS: u32 = glow::LESS;
+    #[quickjs(proto)]
+    pub const EQUAL: u32 = glow::EQUAL;
+    #[quickjs(proto)]
+    pub const LEQUAL: u32 = glow::LEQUAL;
+    #[quickjs(proto)]
+    pub const GREATER: u32 = glow::GREATER;
+    #[quickjs(proto)]
+    pub const NOTEQUAL: u32 = glow::NOTEQUAL;
+    #[quickjs(proto)]
+    pub const GEQUAL: u32 = glow::GEQUAL;
+    #[quickjs(proto)]
+    pub const ALWAYS: u32 = glow::ALWAYS;
+    #[quickjs(proto)]
+    pub const KEEP: u32 = glow::KEEP;
+    #[quickjs(proto)]
+    pub const REPLACE: u32 = glow::REPLACE;
+    #[quickjs(proto)]
+    pub const INCR: u32 = glow::INCR;
+    #[quickjs(proto)]
+    pub const DECR: u32 = glow::DECR;
+    #[quickjs(proto)]
+    pub const INVERT: u32 = glow::INVERT;
+    #[quickjs(proto)]
+    pub const INCR_WRAP: u32 = glow::INCR_WRAP;
+    #[quickjs(proto)]
+    pub const DECR_WRAP: u32 = glow::DECR_WRAP;
+    #[quickjs(proto)]
+    pub const VENDOR: u32 = glow::VENDOR;
+    #[quickjs(proto)]
+    pub const RENDERER: u32 = glow::RENDERER;
+    #[quickjs(proto)]
+    pub const VERSION: u32 = glow::VERSION;
+    #[quickjs(proto)]
+    pub const NEAREST: u32 = glow::NEAREST;
+    #[quickjs(proto)]
+    pub const LINEAR: u32 = glow::LINEAR;
+    #[quickjs(proto)]
+    pub const NEAREST_MIPMAP_NEAREST: u32 = glow::NEAREST_MIPMAP_NEAREST;
+    #[quickjs(proto)]
+    pub const LINEAR_MIPMAP_NEAREST: u32 = glow::LINEAR_MIPMAP_NEAREST;
+    #[quickjs(proto)]
+    pub const NEAREST_MIPMAP_LINEAR: u32 = glow::NEAREST_MIPMAP_LINEAR;
+    #[quickjs(proto)]
+    pub const LINEAR_MIPMAP_LINEAR: u32 = glow::LINEAR_MIPMAP_LINEAR;
+    #[quickjs(proto)]
+    pub const TEXTURE_MAG_FILTER: u32 = glow::TEXTURE_MAG_FILTER;
+    #[quickjs(proto)]
+    pub const TEXTURE_MIN_FILTER: u32 = glow::TEXTURE_MIN_FILTER;
+    #[quickjs(proto)]
+    pub const TEXTURE_WRAP_S: u32 = glow::TEXTURE_WRAP_S;
+    #[quickjs(proto)]
+    pub const TEXTURE_WRAP_T: u32 = glow::TEXTURE_WRAP_T;
+    #[quickjs(proto)]
+    pub const TEXTURE_2D: u32 = glow::TEXTURE_2D;
+    #[quickjs(proto)]
+    pub const TEXTURE: u32 = glow::TEXTURE;
+    #[quickjs(proto)]
+    pub const TEXTURE_CUBE_MAP: u32 = glow::TEXTURE_CUBE_MAP;
+    #[quickjs(proto)]
+    pub const TEXTURE_BINDING_CUBE_MAP: u32 = glow::TEXTURE_BINDING_CUBE_MAP;
+    #[quickjs(proto)]
+    pub const TEXTURE_CUBE_MAP_POSITIVE_X: u32 = glow::TEXTURE_CUBE_MAP_POSITIVE_X;
+    #[quickjs(proto)]
+    pub const TEXTURE_CUBE_MAP_NEGATIVE_X: u32 = glow::TEXTURE_CUBE_MAP_NEGATIVE_X;
+    #[quickjs(proto)]
+    pub const TEXTURE_CUBE_MAP_POSITIVE_Y: u32 = glow::TEXTURE_CUBE_MAP_POSITIVE_Y;
+    #[quickjs(proto)]
+    pub const TEXTURE_CUBE_MAP_NEGATIVE_Y: u32 = glow::TEXTURE_CUBE_MAP_NEGATIVE_Y;
+    #[quickjs(proto)]
+    pub const TEXTURE_CUBE_MAP_POSITIVE_Z: u32 = glow::TEXTURE_CUBE_MAP_POSITIVE_Z;
+    #[quickjs(proto)]
+    pub const TEXTURE_CUBE_MAP_NEGATIVE_Z: u32 = glow::TEXTURE_CUBE_MAP_NEGATIVE_Z;
+    #[quickjs(proto)]
+    pub const MAX_CUBE_MAP_TEXTURE_SIZE: u32 = glow::MAX_CUBE_MAP_TEXTURE_SIZE;
+    #[quickjs(proto)]
+    pub const TEXTURE0: u32 = glow::TEXTURE0;
+    #[quickjs(proto)]
+    pub const TEXTURE1: u32 = glow::TEXTURE1;
+    #[quickjs(proto)]
+    pub const TEXTURE2: u32 = glow::TEXTURE2;
+    #[quickjs(proto)]
+    pub const TEXTURE3: u32 = glow::TEXTURE3;
+    #[quickjs(proto)]
+    pub const TEXTURE4: u32 = glow::TEXTURE4;
+    #[quickjs(proto)]
+    pub const TEXTURE5: u32 = glow::TEXTURE5;
+    #[quickjs(proto)]
+    pub const TEXTURE6: u32 = glow::TEXTURE6;
+    #[quickjs(proto)]
+    pub const TEXTURE7: u32 = glow::TEXTURE7;
+    #[quickjs(proto)]
+    pub const TEXTURE8: u32 = glow::TEXTURE8;
+    #[quickjs(proto)]
+    pub const TEXTURE9: u32 = glow::TEXTURE9;
+    #[quickjs(proto)]
+    pub const TEXTURE10: u32 = glow::TEXTURE10;
+    #[quickjs(proto)]
+    pub const TEXTURE11: u32 = glow::TEXTURE11;
+    #[quickjs(proto)]
+    pub const TEXTURE12: u32 = glow::TEXTURE12;
+    #[quickjs(proto)]
+    pub const TEXTURE13: u32 = glow::TEXTURE13;
+    #[quickjs(proto)]
+    pub const TEXTURE14: u32 = glow::TEXTURE14;
+    #[quickjs(proto)]
+    pub const TEXTURE15: u32 = glow::TEXTURE15;
+    #[quickjs(proto)]
+    pub const TEXTURE16: u32 = glow::TEXTURE16;
+    #[quickjs(proto)]
+    pub const TEXTURE17: u32 = glow::TEXTURE17;
+    #[quickjs(proto)]
+    pub const TEXTURE18: u32 = glow::TEXTURE18;
+    #[quickjs(proto)]
+    pub const TEXTURE19: u32 = glow::TEXTURE19;
+    #[quickjs(proto)]
+    pub const TEXTURE20: u32 = glow::TEXTURE20;
+    #[quickjs(proto)]
+    pub const TEXTURE21: u32 = glow::TEXTURE21;
+    #[quickjs(proto)]
+    pub const TEXTURE22: u32 = glow::TEXTURE22;
+    #[quickjs(proto)]
+    pub const TEXTURE23: u32 = glow::TEXTURE23;
+    #[quickjs(proto)]
+    pub const TEXTURE24: u32 = glow::TEXTURE24;
+    #[quickjs(proto)]
+    pub const TEXTURE25: u32 = glow::TEXTURE25;
+    #[quickjs(proto)]
+    pub const TEXTURE26: u32 = glow::TEXTURE26;
+    #[quickjs(proto)]
+    pub const TEXTURE27: u32 = glow::TEXTURE27;
+    #[quickjs(proto)]
+    pub const TEXTURE28: u32 = glow::TEXTURE28;
+    #[quickjs(proto)]
+    pub const TEXTURE29: u32 = glow::TEXTURE29;
+    #[quickjs(proto)]
+    pub const TEXTURE30: u32 = glow::TEXTURE30;
+    #[quickjs(proto)]
+    pub const TEXTURE31: u32 = glow::TEXTURE31;
+    #[quickjs(proto)]
+    pub const ACTIVE_TEXTURE: u32 = glow::ACTIVE_TEXTURE;
+    #[quickjs(proto)]
+    pub const REPEAT: u32 = glow::REPEAT;
+    #[quickjs(proto)]
+    pub const CLAMP_TO_EDGE: u32 = glow::CLAMP_TO_EDGE;
+    #[quickjs(proto)]
+    pub const MIRRORED_REPEAT: u32 = glow::MIRRORED_REPEAT;
+    #[quickjs(proto)]
+    pub const FLOAT_VEC2: u32 = glow::FLOAT_VEC2;
+    #[quickjs(proto)]
+    pub const FLOAT_VEC3: u32 = glow::FLOAT_VEC3;
+    #[quickjs(proto)]
+    pub const FLOAT_VEC4: u32 = glow::FLOAT_VEC4;
+    #[quickjs(proto)]
+    pub const INT_VEC2: u32 = glow::INT_VEC2;
+    #[quickjs(proto)]
+    pub const INT_VEC3: u32 = glow::INT_VEC3;
+    #[quickjs(proto)]
+    pub const INT_VEC4: u32 = glow::INT_VEC4;
+    #[quickjs(proto)]
+    pub const BOOL: u32 = glow::BOOL;
+    #[quickjs(proto)]
+    pub const BOOL_VEC2: u32 = glow::BOOL_VEC2;
+    #[quickjs(proto)]
+    pub const BOOL_VEC3: u32 = glow::BOOL_VEC3;
+    #[quickjs(proto)]
+    pub const BOOL_VEC4: u32 = glow::BOOL_VEC4;
+    #[quickjs(proto)]
+    pub const FLOAT_MAT2: u32 = glow::FLOAT_MAT2;
+    #[quickjs(proto)]
+    pub const FLOAT_MAT3: u32 = glow::FLOAT_MAT3;
+    #[quickjs(proto)]
+    pub const FLOAT_MAT4: u32 = glow::FLOAT_MAT4;
+    #[quickjs(proto)]
+    pub const SAMPLER_2D: u32 = glow::SAMPLER_2D;
+    #[quickjs(proto)]
+    pub const SAMPLER_CUBE: u32 = glow::SAMPLER_CUBE;
+    #[quickjs(proto)]
+    pub const VERTEX_ATTRIB_ARRAY_ENABLED: u32 = glow::VERTEX_ATTRIB_ARRAY_ENABLED;
+    #[quickjs(proto)]
+    pub const VERTEX_ATTRIB_ARRAY_SIZE: u32 = glow::VERTEX_ATTRIB_ARRAY_SIZE;
+    #[quickjs(proto)]
+    pub const VERTEX_ATTRIB_ARRAY_STRIDE: u32 = glow::VERTEX_ATTRIB_ARRAY_STRIDE;
+    #[quickjs(proto)]
+    pub const VERTEX_ATTRIB_ARRAY_TYPE: u32 = glow::VERTEX_ATTRIB_ARRAY_TYPE;
+    #[quickjs(proto)]
+    pub const VERTEX_ATTRIB_ARRAY_NORMALIZED: u32 = glow::VERTEX_ATTRIB_ARRAY_NORMALIZED;
+    #[quickjs(proto)]
+    pub const VERTEX_ATTRIB_ARRAY_POINTER: u32 = glow::VERTEX_ATTRIB_ARRAY_POINTER;
+    #[quickjs(proto)]
+    pub const VERTEX_ATTRIB_ARRAY_BUFFER_BINDING: u32 = glow::VERTEX_ATTRIB_ARRAY_BUFFER_BINDING;
+    #[quickjs(proto)]
+    pub const IMPLEMENTATION_COLOR_READ_TYPE: u32 = glow::IMPLEMENTATION_COLOR_READ_TYPE;
+    #[quickjs(proto)]
+    pub const IMPLEMENTATION_COLOR_READ_FORMAT: u32 = glow::IMPLEMENTATION_COLOR_READ_FORMAT;
+    #[quickjs(proto)]
+    pub const COMPILE_STATUS: u32 = glow::COMPILE_STATUS;
+    #[quickjs(proto)]
+    pub const LOW_FLOAT: u32 = glow::LOW_FLOAT;
+    #[quickjs(proto)]
+    pub const MEDIUM_FLOAT: u32 = glow::MEDIUM_FLOAT;
+    #[quickjs(proto)]
+    pub const HIGH_FLOAT: u32 = glow::HIGH_FLOAT;
+    #[quickjs(proto)]
+    pub const LOW_INT: u32 = glow::LOW_INT;
+    #[quickjs(proto)]
+    pub const MEDIUM_INT: u32 = glow::MEDIUM_INT;
+    #[quickjs(proto)]
+    pub const HIGH_INT: u32 = glow::HIGH_INT;
+    #[quickjs(proto)]
+    pub const FRAMEBUFFER: u32 = glow::FRAMEBUFFER;
+    #[quickjs(proto)]
+    pub const RENDERBUFFER: u32 = glow::RENDERBUFFER;
+    #[quickjs(proto)]
+    pub const RGBA4: u32 = glow::RGBA4;
+    #[quickjs(proto)]
+    pub const RGB5_A1: u32 = glow::RGB5_A1;
+    #[quickjs(proto)]
+    pub const RGB565: u32 = glow::RGB565;
+    #[quickjs(proto)]
+    pub const DEPTH_COMPONENT16: u32 = glow::DEPTH_COMPONENT16;
+    #[quickjs(proto)]
+    pub const STENCIL_INDEX8: u32 = glow::STENCIL_INDEX8;
+    #[quickjs(proto)]
+    pub const DEPTH_STENCIL: u32 = glow::DEPTH_STENCIL;
+    #[quickjs(proto)]
+    pub const RENDERBUFFER_WIDTH: u32 = glow::RENDERBUFFER_WIDTH;
+    #[quickjs(proto)]
+    pub const RENDERBUFFER_HEIGHT: u32 = glow::RENDERBUFFER_HEIGHT;
+    #[quickjs(proto)]
+    pub const RENDERBUFFER_INTERNAL_FORMAT: u32 = glow::RENDERBUFFER_INTERNAL_FORMAT;
+    #[quickjs(proto)]
+    pub const RENDERBUFFER_RED_SIZE: u32 = glow::RENDERBUFFER_RED_SIZE;
+    #[quickjs(proto)]
+    pub const RENDERBUFFER_GREEN_SIZE: u32 = glow::RENDERBUFFER_GREEN_SIZE;
+    #[quickjs(proto)]
+    pub const RENDERBUFFER_BLUE_SIZE: u32 = glow::RENDERBUFFER_BLUE_SIZE;
+    #[quickjs(proto)]
+    pub const RENDERBUFFER_ALPHA_SIZE: u32 = glow::RENDERBUFFER_ALPHA_SIZE;
+    #[quickjs(proto)]
+    pub const RENDERBUFFER_DEPTH_SIZE: u32 = glow::RENDERBUFFER_DEPTH_SIZE;
+    #[quickjs(proto)]
+    pub const RENDERBUFFER_STENCIL_SIZE: u32 = glow::RENDERBUFFER_STENCIL_SIZE;
+    #[quickjs(proto)]
+    pub const FRAMEBUFFER_ATTACHMENT_OBJECT_TYPE: u32 = glow::FRAMEBUFFER_ATTACHMENT_OBJECT_TYPE;
+    #[quickjs(proto)]
+    pub const FRAMEBUFFER_ATTACHMENT_OBJECT_NAME: u32 = glow::FRAMEBUFFER_ATTACHMENT_OBJECT_NAME;
+    #[quickjs(proto)]
+    pub const FRAMEBUFFER_ATTACHMENT_TEXTURE_LEVEL: u32 =
+      glow::FRAMEBUFFER_ATTACHMENT_TEXTURE_LEVEL;
+    #[quickjs(proto)]
+    pub const FRAMEBUFFER_ATTACHMENT_TEXTURE_CUBE_MAP_FACE: u32 =
+      glow::FRAMEBUFFER_ATTACHMENT_TEXTURE_CUBE_MAP_FACE;
+    #[quickjs(proto)]
+    pub const COLOR_ATTACHMENT0: u32 = glow::COLOR_ATTACHMENT0;
+    #[quickjs(proto)]
+    pub const DEPTH_ATTACHMENT: u32 = glow::DEPTH_ATTACHMENT;
+    #[quickjs(proto)]
+    pub const STENCIL_ATTACHMENT: u32 = glow::STENCIL_ATTACHMENT;
+    #[quickjs(proto)]
+    pub const DEPTH_STENCIL_ATTACHMENT: u32 = glow::DEPTH_STENCIL_ATTACHMENT;
+    #[quickjs(proto)]
+    pub const NONE: u32 = glow::NONE;
+    #[quickjs(proto)]
+    pub const FRAMEBUFFER_COMPLETE: u32 = glow::FRAMEBUFFER_COMPLETE;
+    #[quickjs(proto)]
+    pub const FRAMEBUFFER_INCOMPLETE_ATTACHMENT: u32 = glow::FRAMEBUFFER_INCOMPLETE_ATTACHMENT;
+    #[quickjs(proto)]
+    pub const FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT: u32 =
+      glow::FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT;
+    #[quickjs(proto)]
+    pub const FRAMEBUFFER_INCOMPLETE_DIMENSIONS: u32 = glow::FRAMEBUFFER_INCOMPLETE_DIMENSIONS;
+    #[quickjs(proto)]
+    pub const FRAMEBUFFER_UNSUPPORTED: u32 = glow::FRAMEBUFFER_UNSUPPORTED;
+    #[quickjs(proto)]
+    pub const FRAMEBUFFER_BINDING: u32 = glow::FRAMEBUFFER_BINDING;
+    #[quickjs(proto)]
+    pub const RENDERBUFFER_BINDING: u32 = glow::RENDERBUFFER_BINDING;
+    #[quickjs(proto)]
+    pub const MAX_RENDERBUFFER_SIZE: u32 = glow::MAX_RENDERBUFFER_SIZE;
+    #[quickjs(proto)]
+    pub const INVALID_FRAMEBUFFER_OPERATION: u32 = glow::INVALID_FRAMEBUFFER_OPERATION;
+    #[quickjs(proto)]
+    pub const VERTEX_ARRAY_BINDING: u32 = glow::VERTEX_ARRAY_BINDING;
+
+    #[quickjs(skip)]
+    pub fn new(gl: &Rc<Context>, assets: PathBuf) -> Self {
+      Self {
+        gl: gl.clone(),
+        assets,
+        attributes: RefCell::new(HashMap::new()),
+      }
+    }
+
+    #[quickjs(skip)]
+    fn binding(&self, parameter: u32) -> u32 {
+      unsafe { self.gl.get_parameter_i32(parameter) as u32 }
+    }
+
+    #[quickjs(skip)]
+    fn attribute(&self, index: u32, f: impl FnOnce(&mut AttributeState)) {
+      let vertex_array = self.binding(VERTEX_ARRAY_BINDING);
+      let mut attributes = self.attributes.borrow_mut();
+      f(attributes.entry((vertex_array, index)).or_default());
+    }
+
+    // WebGL's INVALID_OPERATION checks, without them GL takes offsets for client pointers
+    #[quickjs(skip)]
+    fn check_draw(&self, ctx: Ctx<'_>, call: &str, elements: bool) -> Result<()> {
+      if elements && self.binding(ELEMENT_ARRAY_BUFFER_BINDING) == 0 {
+        return Err(Exception::throw_message(
+          ctx,
+          &format!("{}: no buffer is bound to ELEMENT_ARRAY_BUFFER", call),
+        ));
+      }
+      let vertex_array = self.binding(VERTEX_ARRAY_BINDING);
+      match unbacked_attribute(&self.attributes.borrow(), vertex_array) {
+        Some(index) => Err(Exception::throw_message(
+          ctx,
+          &format!(
+            "{}: attribute {} is enabled without a vertexAttribPointer buffer",
+            call, index
+          ),
+        )),
+        None => Ok(()),
+      }
+    }
+
+    // state
+
+    pub fn enable(&self, capability: u32) {
+      unsafe { self.gl.enable(capability) }
+    }
+    pub fn disable(&self, capability: u32) {
+      unsafe { self.gl.disable(capability) }
+    }
+    #[quickjs(rename = "isEnabled")]
+    pub fn is_enabled(&self, capability: u32) -> bool {
+      unsafe { self.gl.is_enabled(capability) }
+    }
+    #[quickjs(rename = "getError")]
+    pub fn get_error(&self) -> u32 {
+      unsafe { self.gl.get_error() }
+    }
+    #[quickjs(rename = "getParameter")]
+    pub fn get_parameter<'js>(&self, ctx: Ctx<'js>, parameter: u32) -> Result<Value<'js>> {
+      use rquickjs::IntoJs;
+      unsafe {
+        match parameter {
+          VENDOR | RENDERER | VERSION | SHADING_LANGUAGE_VERSION => {
+            self.gl.get_parameter_string(parameter).into_js(ctx)
+          }
+          VIEWPORT | SCISSOR_BOX => {
+            let mut values = [0; 4];
+            self.gl.get_parameter_i32_slice(parameter, &mut values);
+            values.to_vec().into_js(ctx)
+          }
+          MAX_VIEWPORT_DIMS => {
+            let mut values = [0; 2];
+            self.gl.get_parameter_i32_slice(parameter, &mut values);
+            values.to_vec().into_js(ctx)
+          }
+          COLOR_CLEAR_VALUE | BLEND_COLOR => {
+            let mut values = [0.; 4];
+            self.gl.get_parameter_f32_slice(parameter, &mut values);
+            values.to_vec().into_js(ctx)
+          }
+          DEPTH_CLEAR_VALUE | LINE_WIDTH | POLYGON_OFFSET_FACTOR | POLYGON_OFFSET_UNITS => {
+            self.gl.get_parameter_f32(parameter).into_js(ctx)
+          }
+          BLEND | CULL_FACE | DEPTH_TEST | DITHER | SCISSOR_TEST | STENCIL_TEST
+          | POLYGON_OFFSET_FILL | DEPTH_WRITEMASK => {
+            (self.gl.get_parameter_i32(parameter) != 0).into_js(ctx)
+          }
+          _ => self.gl.get_parameter_i32(parameter).into_js(ctx),
+        }
+      }
+    }
+    pub fn viewport(&self, x: i32, y: i32, width: i32, height: i32) {
+      unsafe { self.gl.viewport(x, y, width, height) }
+    }
+    pub fn scissor(&self, x: i32, y: i32, width: i32, height: i32) {
+      unsafe { self.gl.scissor(x, y, width, height) }
+    }
+    pub fn clear(&self, mask: u32) {
+      unsafe {
+        self.gl.clear(mask);
+      }
+    }
+    #[quickjs(rename = "clearColor")]
+    pub fn clear_color(&self, r: f32, g: f32, b: f32, a: f32) {
+      unsafe {
+        self.gl.clear_color(r, g, b, a);
+      }
+    }
+    #[quickjs(rename = "clearDepth")]
+    pub fn clear_depth(&self, depth: f32) {
+      unsafe { self.gl.clear_depth_f32(depth) }
+    }
+    #[quickjs(rename = "clearStencil")]
+    pub fn clear_stencil(&self, stencil: i32) {
+      unsafe { self.gl.clear_stencil(stencil) }
+    }
+    #[quickjs(rename = "colorMask")]
+    pub fn color_mask(&self, red: bool, green: bool, blue: bool, alpha: bool) {
+      unsafe { self.gl.color_mask(red, green, blue, alpha) }
+    }
+    #[quickjs(rename = "depthFunc")]
+    pub fn depth_func(&self, func: u32) {
+      unsafe { self.gl.depth_func(func) }
+    }
+    #[quickjs(rename = "depthMask")]
+    pub fn depth_mask(&self, flag: bool) {
+      unsafe { self.gl.depth_mask(flag) }
+    }
+    #[quickjs(rename = "depthRange")]
+    pub fn depth_range(&self, near: f32, far: f32) {
+      unsafe { self.gl.depth_range_f32(near, far) }
+    }
+    #[quickjs(rename = "stencilFunc")]
+    pub fn stencil_func(&self, func: u32, reference: i32, mask: u32) {
+      unsafe { self.gl.stencil_func(func, reference, mask) }
+    }
+    #[quickjs(rename = "stencilFuncSeparate")]
+    pub fn stencil_func_separate(&self, face: u32, func: u32, reference: i32, mask: u32) {
+      unsafe { self.gl.stencil_func_separate(face, func, reference, mask) }
+    }
+    #[quickjs(rename = "stencilMask")]
+    pub fn stencil_mask(&self, mask: u32) {
+      unsafe { self.gl.stencil_mask(mask) }
+    }
+    #[quickjs(rename = "stencilMaskSeparate")]
+    pub fn stencil_mask_separate(&self, face: u32, mask: u32) {
+      unsafe { self.gl.stencil_mask_separate(face, mask) }
+    }
+    #[quickjs(rename = "stencilOp")]
+    pub fn stencil_op(&self, fail: u32, zfail: u32, zpass: u32) {
+      unsafe { self.gl.stencil_op(fail, zfail, zpass) }
+    }
+    #[quickjs(rename = "stencilOpSeparate")]
+    pub fn stencil_op_separate(&self, face: u32, fail: u32, zfail: u32, zpass: u32) {
+      unsafe { self.gl.stencil_op_separate(face, fail, zfail, zpass) }
+    }
+    #[quickjs(rename = "cullFace")]
+    pub fn cull_face(&self, mode: u32) {
+      unsafe { self.gl.cull_face(mode) }
+    }
+    #[quickjs(rename = "frontFace")]
+    pub fn front_face(&self, mode: u32) {
+      unsafe { self.gl.front_face(mode) }
+    }
+    #[quickjs(rename = "lineWidth")]
+    pub fn line_width(&self, width: f32) {
+      unsafe { self.gl.line_width(width) }
+    }
+    #[quickjs(rename = "polygonOffset")]
+    pub fn polygon_offset(&self, factor: f32, units: f32) {
+      unsafe { self.gl.polygon_offset(factor, units) }
+    }
+    #[quickjs(rename = "pixelStorei")]
+    // `texImage2D` and `texSubImage2D` read UNPACK_ALIGNMENT back to size their checks
+    pub fn pixel_store_i(&self, parameter: u32, value: i32) {
+      unsafe { self.gl.pixel_store_i32(parameter, value) }
+    }
+    #[quickjs(rename = "blendColor")]
+    pub fn blend_color(&self, r: f32, g: f32, b: f32, a: f32) {
+      unsafe { self.gl.blend_color(r, g, b, a) }
+    }
+    #[quickjs(rename = "blendEquation")]
+    pub fn blend_equation(&self, mode: u32) {
+      unsafe { self.gl.blend_equation(mode) }
+    }
+    #[quickjs(rename = "blendEquationSeparate")]
+    pub fn blend_equation_separate(&self, mode_rgb: u32, mode_alpha: u32) {
+      unsafe { self.gl.blend_equation_separate(mode_rgb, mode_alpha) }
+    }
+    #[quickjs(rename = "blendFunc")]
+    pub fn blend_func(&self, src: u32, dst: u32) {
+      unsafe { self.gl.blend_func(src, dst) }
+    }
+    #[quickjs(rename = "blendFuncSeparate")]
+    pub fn blend_func_separate(&self, src_rgb: u32, dst_rgb: u32, src_alpha: u32, dst_alpha: u32) {
+      unsafe {
+        self
+          .gl
+          .blend_func_separate(src_rgb, dst_rgb, src_alpha, dst_alpha)
+      }
+    }
+    pub fn flush(&self) {
+      unsafe { self.gl.flush() }
+    }
+    pub fn finish(&self) {
+      unsafe { self.gl.finish() }
+    }
+
+    // buffers
+
+    #[quickjs(rename = "createBuffer")]
+    pub fn create_buffer(&self) -> Option<u32> {
+      unsafe { self.gl.create_buffer().ok().map(|buffer| buffer.0.get()) }
+    }
+    #[quickjs(rename = "deleteBuffer")]
+    pub fn delete_buffer(&self, buffer: Option<u32>) {
+      if let Some(native) = handle(buffer, NativeBuffer) {
+        unsafe { self.gl.delete_buffer(native) };
+        for state in self.attributes.borrow_mut().values_mut() {
+          if state.buffer == buffer {
+            state.buffer = None;
+          }
+        }
+      }
+    }
+    #[quickjs(rename = "isBuffer")]
+    pub fn is_buffer(&self, buffer: Option<u32>) -> bool {
+      match handle(buffer, NativeBuffer) {
+        Some(buffer) => unsafe { self.gl.is_buffer(buffer) },
+        None => false,
+      }
+    }
+    #[quickjs(rename = "bindBuffer")]
+    pub fn bind_buffer(&self, target: u32, buffer: Option<u32>) {
+      unsafe { self.gl.bind_buffer(target, handle(buffer, NativeBuffer)) }
+    }
+    // `data` is either a byte size to allocate or the data to upload
+    #[quickjs(rename = "bufferData")]
+    pub fn buffer_data(&self, target: u32, data: Value, usage: u32) -> Result<()> {
+      match data.as_number() {
+        Some(size) => unsafe { self.gl.buffer_data_size(target, size as i32, usage) },
+        None => with_bytes(&data, |bytes| unsafe {
+          self.gl.buffer_data_u8_slice(target, bytes, usage)
+        })?,
+      }
+      Ok(())
+    }
+    #[quickjs(rename = "bufferSubData")]
+    pub fn buffer_sub_data(&self, target: u32, offset: i32, data: Value) -> Result<()> {
+      with_bytes(&data, |bytes| unsafe {
+        self.gl.buffer_sub_data_u8_slice(target, offset, bytes)
+      })
+    }
+
+    // vertex arrays
+
+    #[quickjs(rename = "createVertexArray")]
+    pub fn create_vertex_array(&self) -> Option<u32> {
+      unsafe {
+        self
+          .gl
+          .create_vertex_array()
+          .ok()
+          .map(|vertex_array| vertex_array.0.get())
+      }
+    }
+    #[quickjs(rename = "deleteVertexArray")]
+    pub fn delete_vertex_array(&self, vertex_array: Option<u32>) {
+      if let Some(native) = handle(vertex_array, NativeVertexArray) {
+        unsafe { self.gl.delete_vertex_array(native) };
+        let id = native.0.get();
+        self
+          .attributes
+          .borrow_mut()
+          .retain(|(array, _), _| *array != id);
+      }
+    }
+    #[quickjs(rename = "bindVertexArray")]
+    pub fn bind_vertex_array(&self, vertex_array: Option<u32>) {
+      unsafe {
+        self
+          .gl
+          .bind_vertex_array(handle(vertex_array, NativeVertexArray))
+      }
+    }
+
+    // shaders and programs
+
+    #[quickjs(rename = "createShader")]
+    pub fn create_shader(&self, shader_type: u32) -> Option<u32> {
+      unsafe {
+        self
+          .gl
+          .create_shader(shader_type)
+          .ok()
+          .map(|shader| shader.0.get())
+      }
+    }
+    #[quickjs(rename = "deleteShader")]
+    pub fn delete_shader(&self, shader: Option<u32>) {
+      match handle(shader, NativeShader) {
+        Some(shader) => unsafe { self.gl.delete_shader(shader) },
+        None => (),
+      }
+    }
+    #[quickjs(rename = "shaderSource")]
+    pub fn shader_source(&self, shader: u32, source: String) {
+      match handle(Some(shader), NativeShader) {
+        Some(shader) => unsafe { self.gl.shader_source(shader, &source) },
+        None => (),
+      }
+    }
+    #[quickjs(rename = "compileShader")]
+    pub fn compile_shader(&self, shader: u32) {
+      match handle(Some(shader), NativeShader) {
+        Some(shader) => unsafe { self.gl.compile_shader(shader) },
+        None => (),
+      }
+    }
+    // only COMPILE_STATUS is answered, anything else is null
+    #[quickjs(rename = "getShaderParameter")]
+    pub fn get_shader_parameter(&self, shader: u32, parameter: u32) -> Option<bool> {
+      match (handle(Some(shader), NativeShader), parameter) {
+        (Some(shader), COMPILE_STATUS) => unsafe {
+          Some(self.gl.get_shader_compile_status(shader))
+        },
+        _ => None,
+      }
+    }
+    #[quickjs(rename = "getShaderInfoLog")]
+    pub fn get_shader_info_log(&self, shader: u32) -> Option<String> {
+      handle(Some(shader), NativeShader)
+        .map(|shader| unsafe { self.gl.get_shader_info_log(shader) })
+    }
+    #[quickjs(rename = "createProgram")]
+    pub fn create_program(&self) -> Option<u32> {
+      unsafe { self.gl.create_program().ok().map(|program| program.0.get()) }
+    }
+    #[quickjs(rename = "deleteProgram")]
+    pub fn delete_program(&self, program: Option<u32>) {
+      match handle(program, NativeProgram) {
+        Some(program) => unsafe { self.gl.delete_program(program) },
+        None => (),
+      }
+    }
+    #[quickjs(rename = "attachShader")]
+    pub fn attach_shader(&self, program: u32, shader: u32) {
+      match (
+        handle(Some(program), NativeProgram),
+        handle(Some(shader), NativeShader),
+      ) {
+        (Some(program), Some(shader)) => unsafe { self.gl.attach_shader(program, shader) },
+        _ => (),
+      }
+    }
+    #[quickjs(rename = "detachShader")]
+    pub fn detach_shader(&self, program: u32, shader: u32) {
+      match (
+        handle(Some(program), NativeProgram),
+        handle(Some(shader), NativeShader),
+      ) {
+        (Some(program), Some(shader)) => unsafe { self.gl.detach_shader(program, shader) },
+        _ => (),
+      }
+    }
+    #[quickjs(rename = "bindAttribLocation")]
+    pub fn bind_attrib_location(&self, program: u32, index: u32, name: String) {
+      match handle(Some(program), NativeProgram) {
+        Some(program) => unsafe { self.gl.bind_attrib_location(program, index, &name) },
+        None => (),
+      }
+    }
+    #[quickjs(rename = "linkProgram")]
+    pub fn link_program(&self, program: u32) {
+      match handle(Some(program), NativeProgram) {
+        Some(program) => unsafe { self.gl.link_program(program) },
+        None => (),
+      }
+    }
+    #[quickjs(rename = "useProgram")]
+    pub fn use_program(&self, program: Option<u32>) {
+      unsafe { self.gl.use_program(handle(program, NativeProgram)) }
+    }
+    // LINK_STATUS and the active attribute/uniform counts, anything else is null
+    #[quickjs(rename = "getProgramParameter")]
+    pub fn get_program_parameter<'js>(
+      &self,
+      ctx: Ctx<'js>,
+      program: u32,
+      parameter: u32,
+    ) -> Result<Value<'js>> {
+      use rquickjs::IntoJs;
+      let program = match handle(Some(program), NativeProgram) {
+        Some(program) => program,
+        None => return Ok(Value::new_null(ctx)),
+      };
+      unsafe {
+        match parameter {
+          LINK_STATUS => self.gl.get_program_link_status(program).into_js(ctx),
+          ACTIVE_ATTRIBUTES => self.gl.get_active_attributes(program).into_js(ctx),
+          ACTIVE_UNIFORMS => self.gl.get_active_uniforms(program).into_js(ctx),
+          _ => Ok(Value::new_null(ctx)),
+        }
+      }
+    }
+    #[quickjs(rename = "getProgramInfoLog")]
+    pub fn get_program_info_log(&self, program: u32) -> Option<String> {
+      handle(Some(program), NativeProgram)
+        .map(|program| unsafe { self.gl.get_program_info_log(program) })
+    }
+    #[quickjs(rename = "getAttribLocation")]
+    pub fn get_attrib_location(&self, program: u32, name: String) -> i32 {
+      match handle(Some(program), NativeProgram) {
+        Some(program) => unsafe {
+          self
+            .gl
+            .get_attrib_location(program, &name)
+            .map_or(-1, |location| location as i32)
+        },
+        None => -1,
+      }
+    }
+    #[quickjs(rename = "getUniformLocation")]
+    pub fn get_uniform_location(&self, program: u32, name: String) -> Option<u32> {
+      let program = handle(Some(program), NativeProgram)?;
+      unsafe {
+        self
+          .gl
+          .get_uniform_location(program, &name)
+          .map(|location| location.0)
+      }
+    }
+
+    // vertex attributes
+
+    #[quickjs(rename = "enableVertexAttribArray")]
+    pub fn enable_vertex_attrib_array(&self, index: u32) {
+      self.attribute(index, |state| state.enabled = true);
+      unsafe { self.gl.enable_vertex_attrib_array(index) }
+    }
+    #[quickjs(rename = "disableVertexAttribArray")]
+    pub fn disable_vertex_attrib_array(&self, index: u32) {
+      self.attribute(index, |state| state.enabled = false);
+      unsafe { self.gl.disable_vertex_attrib_array(index) }
+    }
+    #[quickjs(rename = "vertexAttribPointer")]
+    // `offset` is into the buffer bound to ARRAY_BUFFER, there has to be one, with a `ctx`
+    // this is one argument more than a bound method can take
+    pub fn vertex_attrib_pointer(
+      &self,
+      index: u32,
+      size: i32,
+      data_type: u32,
+      normalized: bool,
+      stride: i32,
+      offset: i32,
+    ) -> Result<()> {
+      let buffer = self.binding(ARRAY_BUFFER_BINDING);
+      if buffer == 0 {
+        return Err(Error::new_from_js_message(
+          "number",
+          "offset",
+          "vertexAttribPointer: no buffer is bound to ARRAY_BUFFER",
+        ));
+      }
+      self.attribute(index, |state| state.buffer = Some(buffer));
+      unsafe {
+        self
+          .gl
+          .vertex_attrib_pointer_f32(index, size, data_type, normalized, stride, offset)
+      }
+      Ok(())
+    }
+    #[quickjs(rename = "vertexAttrib1f")]
+    pub fn vertex_attrib_1f(&self, index: u32, x: f32) {
+      unsafe { self.gl.vertex_attrib_1_f32(index, x) }
+    }
+    #[quickjs(rename = "vertexAttrib2f")]
+    pub fn vertex_attrib_2f(&self, index: u32, x: f32, y: f32) {
+      unsafe { self.gl.vertex_attrib_2_f32(index, x, y) }
+    }
+    #[quickjs(rename = "vertexAttrib3f")]
+    pub fn vertex_attrib_3f(&self, index: u32, x: f32, y: f32, z: f32) {
+      unsafe { self.gl.vertex_attrib_3_f32(index, x, y, z) }
+    }
+    #[quickjs(rename = "vertexAttrib4f")]
+    pub fn vertex_attrib_4f(&self, index: u32, x: f32, y: f32, z: f32, w: f32) {
+      unsafe { self.gl.vertex_attrib_4_f32(index, x, y, z, w) }
+    }
+    #[quickjs(rename = "vertexAttrib4fv")]
+    // GL reads 4 values whatever the length
+    pub fn vertex_attrib_4fv<'js>(&self, ctx: Ctx<'js>, index: u32, values: Value) -> Result<()> {
+      with_f32s(&values, |values| match values.len() {
+        0..=3 => Err(Exception::throw_message(
+          ctx,
+          &format!("vertexAttrib4fv: 4 values needed, {} given", values.len()),
+        )),
+        _ => {
+          unsafe { self.gl.vertex_attrib_4_f32_slice(index, &values[..4]) };
+          Ok(())
+        }
+      })?
+    }
+
+    // uniforms
+
+    #[quickjs(rename = "uniform1f")]
+    pub fn uniform_1f(&self, location: Option<u32>, x: f32) {
+      let location = location.map(NativeUniformLocation);
+      unsafe { self.gl.uniform_1_f32(location.as_ref(), x) }
+    }
+    #[quickjs(rename = "uniform2f")]
+    pub fn uniform_2f(&self, location: Option<u32>, x: f32, y: f32) {
+      let location = location.map(NativeUniformLocation);
+      unsafe { self.gl.uniform_2_f32(location.as_ref(), x, y) }
+    }
+    #[quickjs(rename = "uniform3f")]
+    pub fn uniform_3f(&self, location: Option<u32>, x: f32, y: f32, z: f32) {
+      let location = location.map(NativeUniformLocation);
+      unsafe { self.gl.uniform_3_f32(location.as_ref(), x, y, z) }
+    }
+    #[quickjs(rename = "uniform4f")]
+    pub fn uniform_4f(&self, location: Option<u32>, x: f32, y: f32, z: f32, w: f32) {
+      let location = location.map(NativeUniformLocation);
+      unsafe { self.gl.uniform_4_f32(location.as_ref(), x, y, z, w) }
+    }
+    #[quickjs(rename = "uniform1i")]
+    pub fn uniform_1i(&self, location: Option<u32>, x: i32) {
+      let location = location.map(NativeUniformLocation);
+      unsafe { self.gl.uniform_1_i32(location.as_ref(), x) }
+    }
+    #[quickjs(rename = "uniform2i")]
+    pub fn uniform_2i(&self, location: Option<u32>, x: i32, y: i32) {
+      let location = location.map(NativeUniformLocation);
+      unsafe { self.gl.uniform_2_i32(location.as_ref(), x, y) }
+    }
+    #[quickjs(rename = "uniform3i")]
+    pub fn uniform_3i(&self, location: Option<u32>, x: i32, y: i32, z: i32) {
+      let location = location.map(NativeUniformLocation);
+      unsafe { self.gl.uniform_3_i32(location.as_ref(), x, y, z) }
+    }
+    #[quickjs(rename = "uniform4i")]
+    pub fn uniform_4i(&self, location: Option<u32>, x: i32, y: i32, z: i32, w: i32) {
+      let location = location.map(NativeUniformLocation);
+      unsafe { self.gl.uniform_4_i32(location.as_ref(), x, y, z, w) }
+    }
+    #[quickjs(rename = "uniform1fv")]
+    pub fn uniform_1fv(&self, location: Option<u32>, values: Value) -> Result<()> {
+      let location = location.map(NativeUniformLocation);
+      with_f32s(&values, |values| unsafe {
+        self.gl.uniform_1_f32_slice(location.as_ref(), values)
+      })
+    }
+    #[quickjs(rename = "uniform2fv")]
+    pub fn uniform_2fv(&self, location: Option<u32>, values: Value) -> Result<()> {
+      let location = location.map(NativeUniformLocation);
+      with_f32s(&values, |values| unsafe {
+        self.gl.uniform_2_f32_slice(location.as_ref(), values)
+      })
+    }
+    #[quickjs(rename = "uniform3fv")]
+    pub fn uniform_3fv(&self, location: Option<u32>, values: Value) -> Result<()> {
+      let location = location.map(NativeUniformLocation);
+      with_f32s(&values, |values| unsafe {
+        self.gl.uniform_3_f32_slice(location.as_ref(), values)
+      })
+    }
+    #[quickjs(rename = "uniform4fv")]
+    pub fn uniform_4fv(&self, location: Option<u32>, values: Value) -> Result<()> {
+      let location = location.map(NativeUniformLocation);
+      with_f32s(&values, |values| unsafe {
+        self.gl.uniform_4_f32_slice(location.as_ref(), values)
+      })
+    }
+    #[quickjs(rename = "uniform1iv")]
+    pub fn uniform_1iv(&self, location: Option<u32>, values: Value) -> Result<()> {
+      let location = location.map(NativeUniformLocation);
+      with_i32s(&values, |values| unsafe {
+        self.gl.uniform_1_i32_slice(location.as_ref(), values)
+      })
+    }
+    #[quickjs(rename = "uniform2iv")]
+    pub fn uniform_2iv(&self, location: Option<u32>, values: Value) -> Result<()> {
+      let location = location.map(NativeUniformLocation);
+      with_i32s(&values, |values| unsafe {
+        self.gl.uniform_2_i32_slice(location.as_ref(), values)
+      })
+    }
+    #[quickjs(rename = "uniform3iv")]
+    pub fn uniform_3iv(&self, location: Option<u32>, values: Value) -> Result<()> {
+      let location = location.map(NativeUniformLocation);
+      with_i32s(&values, |values| unsafe {
+        self.gl.uniform_3_i32_slice(location.as_ref(), values)
+      })
+    }
+    #[quickjs(rename = "uniform4iv")]
+    pub fn uniform_4iv(&self, location: Option<u32>, values: Value) -> Result<()> {
+      let location = location.map(NativeUniformLocation);
+      with_i32s(&values, |values| unsafe {
+        self.gl.uniform_4_i32_slice(location.as_ref(), values)
+      })
+    }
+    #[quickjs(rename = "uniformMatrix2fv")]
+    pub fn uniform_matrix_2fv(
+      &self,
+      location: Option<u32>,
+      transpose: bool,
+      values: Value,
+    ) -> Result<()> {
+      let location = location.map(NativeUniformLocation);
+      with_f32s(&values, |values| unsafe {
+        self
+          .gl
+          .uniform_matrix_2_f32_slice(location.as_ref(), transpose, values)
+      })
+    }
+    #[quickjs(rename = "uniformMatrix3fv")]
+    pub fn uniform_matrix_3fv(
+      &self,
+      location: Option<u32>,
+      transpose: bool,
+      values: Value,
+    ) -> Result<()> {
+      let location = location.map(NativeUniformLocation);
+      with_f32s(&values, |values| unsafe {
+        self
+          .gl
+          .uniform_matrix_3_f32_slice(location.as_ref(), transpose, values)
+      })
+    }
+    #[quickjs(rename = "uniformMatrix4fv")]
+    pub fn uniform_matrix_4fv(
+      &self,
+      location: Option<u32>,
+      transpose: bool,
+      values: Value,
+    ) -> Result<()> {
+      let location = location.map(NativeUniformLocation);
+      with_f32s(&values, |values| unsafe {
+        self
+          .gl
+          .uniform_matrix_4_f32_slice(location.as_ref(), transpose, values)
+      })
+    }
+
+    // textures
+
+    #[quickjs(rename = "createTexture")]
+    pub fn create_texture(&self) -> Option<u32> {
+      unsafe { self.gl.create_texture().ok().map(|texture| texture.0.get()) }
+    }
+    #[quickjs(rename = "deleteTexture")]
+    pub fn delete_texture(&self, texture: Option<u32>) {
+      match handle(texture, NativeTexture) {
+        Some(texture) => unsafe { self.gl.delete_texture(texture) },
+        None => (),
+      }
+    }
+    #[quickjs(rename = "bindTexture")]
+    pub fn bind_texture(&self, target: u32, texture: Option<u32>) {
+      unsafe { self.gl.bind_texture(target, handle(texture, NativeTexture)) }
+    }
+    #[quickjs(rename = "activeTexture")]
+    pub fn active_texture(&self, unit: u32) {
+      unsafe { self.gl.active_texture(unit) }
+    }
+    #[quickjs(rename = "texParameteri")]
+    pub fn tex_parameter_i(&self, target: u32, parameter: u32, value: i32) {
+      unsafe { self.gl.tex_parameter_i32(target, parameter, value) }
+    }
+    #[quickjs(rename = "texParameterf")]
+    pub fn tex_parameter_f(&self, target: u32, parameter: u32, value: f32) {
+      unsafe { self.gl.tex_parameter_f32(target, parameter, value) }
+    }
+    // the 9 argument form, `pixels` may be null to only allocate
+    #[quickjs(rename = "texImage2D")]
+    pub fn tex_image_2d(
+      &self,
+      target: u32,
+      level: i32,
+      internal_format: i32,
+      width: i32,
+      height: i32,
+      border: i32,
+      format: u32,
+      data_type: u32,
+      pixels: Opt<Value>,
+    ) -> Result<()> {
+      let upload = |pixels: Option<&[u8]>| unsafe {
+        self.gl.tex_image_2d(
+          target,
+          level,
+          internal_format,
+          width,
+          height,
+          border,
+          format,
+          data_type,
+          pixels,
+        )
+      };
+      match non_null(&pixels.0) {
+        Some(pixels) => with_bytes(pixels, |bytes| {
+          let alignment = self.binding(UNPACK_ALIGNMENT) as usize;
+          check_pixels(bytes, width, height, format, data_type, alignment)?;
+          upload(Some(bytes));
+          Ok(())
+        })?,
+        None => Ok(upload(None)),
+      }
+    }
+    #[quickjs(rename = "texSubImage2D")]
+    pub fn tex_sub_image_2d(
+      &self,
+      target: u32,
+      level: i32,
+      x: i32,
+      y: i32,
+      width: i32,
+      height: i32,
+      format: u32,
+      data_type: u32,
+      pixels: Value,
+    ) -> Result<()> {
+      let alignment = self.binding(UNPACK_ALIGNMENT) as usize;
+      with_bytes(&pixels, |bytes| unsafe {
+        check_pixels(bytes, width, height, format, data_type, alignment)?;
+        self.gl.tex_sub_image_2d(
+          target,
+          level,
+          x,
+          y,
+          width,
+          height,
+          format,
+          data_type,
+          PixelUnpackData::Slice(bytes),
+        );
+        Ok(())
+      })?
+    }
+    #[quickjs(rename = "generateMipmap")]
+    pub fn generate_mipmap(&self, target: u32) {
+      unsafe { self.gl.generate_mipmap(target) }
+    }
+
+    // framebuffers and renderbuffers
+
+    #[quickjs(rename = "createFramebuffer")]
+    pub fn create_framebuffer(&self) -> Option<u32> {
+      unsafe {
+        self
+          .gl
+          .create_framebuffer()
+          .ok()
+          .map(|framebuffer| framebuffer.0.get())
+      }
+    }
+    #[quickjs(rename = "deleteFramebuffer")]
+    pub fn delete_framebuffer(&self, framebuffer: Option<u32>) {
+      match handle(framebuffer, NativeFramebuffer) {
+        Some(framebuffer) => unsafe { self.gl.delete_framebuffer(framebuffer) },
+        None => (),
+      }
+    }
+    #[quickjs(rename = "bindFramebuffer")]
+    pub fn bind_framebuffer(&self, target: u32, framebuffer: Option<u32>) {
+      unsafe {
+        self
+          .gl
+          .bind_framebuffer(target, handle(framebuffer, NativeFramebuffer))
+      }
+    }
+    #[quickjs(rename = "framebufferTexture2D")]
+    pub fn framebuffer_texture_2d(
+      &self,
+      target: u32,
+      attachment: u32,
+      texture_target: u32,
+      texture: Option<u32>,
+      level: i32,
+    ) {
+      unsafe {
+        self.gl.framebuffer_texture_2d(
+          target,
+          attachment,
+          texture_target,
+          handle(texture, NativeTexture),
+          level,
+        )
+      }
+    }
+    #[quickjs(rename = "checkFramebufferStatus")]
+    pub fn check_framebuffer_status(&self, target: u32) -> u32 {
+      unsafe { self.gl.check_framebuffer_status(target) }
+    }
+    #[quickjs(rename = "createRenderbuffer")]
+    pub fn create_renderbuffer(&self) -> Option<u32> {
+      unsafe {
+        self
+          .gl
+          .create_renderbuffer()
+          .ok()
+          .map(|renderbuffer| renderbuffer.0.get())
+      }
+    }
+    #[quickjs(rename = "deleteRenderbuffer")]
+    pub fn delete_renderbuffer(&self, renderbuffer: Option<u32>) {
+      match handle(renderbuffer, NativeRenderbuffer) {
+        Some(renderbuffer) => unsafe { self.gl.delete_renderbuffer(renderbuffer) },
+        None => (),
+      }
+    }
+    #[quickjs(rename = "bindRenderbuffer")]
+    pub fn bind_renderbuffer(&self, target: u32, renderbuffer: Option<u32>) {
+      unsafe {
+        self
+          .gl
+          .bind_renderbuffer(target, handle(renderbuffer, NativeRenderbuffer))
+      }
+    }
+    #[quickjs(rename = "renderbufferStorage")]
+    pub fn renderbuffer_storage(&self, target: u32, internal_format: u32, width: i32, height: i32) {
+      unsafe {
+        self
+          .gl
+          .renderbuffer_storage(target, internal_format, width, height)
+      }
+    }
+    #[quickjs(rename = "framebufferRenderbuffer")]
+    pub fn framebuffer_renderbuffer(
+      &self,
+      target: u32,
+      attachment: u32,
+      renderbuffer_target: u32,
+      renderbuffer: Option<u32>,
+    ) {
+      unsafe {
+        self.gl.framebuffer_renderbuffer(
+          target,
+          attachment,
+          renderbuffer_target,
+          handle(renderbuffer, NativeRenderbuffer),
+        )
+      }
+    }
+
+    // drawing
+
+    #[quickjs(rename = "drawArrays")]
+    pub fn draw_arrays<'js>(&self, ctx: Ctx<'js>, mode: u32, first: i32, count: i32) -> Result<()> {
+      self.check_draw(ctx, "drawArrays", false)?;
+      unsafe { self.gl.draw_arrays(mode, first, count) };
+      Ok(())
+    }
+    // `offset` is into the buffer bound to ELEMENT_ARRAY_BUFFER, there has to be one
+    #[quickjs(rename = "drawElements")]
+    pub fn draw_elements<'js>(
+      &self,
+      ctx: Ctx<'js>,
+      mode: u32,
+      count: i32,
+      element_type: u32,
+      offset: i32,
+    ) -> Result<()> {
+      self.check_draw(ctx, "drawElements", true)?;
+      unsafe { self.gl.draw_elements(mode, count, element_type, offset) };
+      Ok(())
+    }
+  }
+}
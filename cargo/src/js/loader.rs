@@ -0,0 +1,79 @@
+use rquickjs::{
+  loader::{Loader, Resolver},
+  module::ModuleData,
+  Ctx, Error, Result,
+};
+use std::path::{Component, Path, PathBuf};
+
+// module names are paths relative to the asset root, e.g. "main.js" or "enemies/slime.js",
+// so they read well in error messages and stay the same on every platform
+pub struct AssetResolver;
+
+impl Resolver for AssetResolver {
+  fn resolve<'js>(&mut self, _ctx: Ctx<'js>, base: &str, name: &str) -> Result<String> {
+    // "./x" and "../x" are relative to the importing module, anything else to the asset root
+    let joined = if name.starts_with("./") || name.starts_with("../") {
+      Path::new(base).parent().unwrap_or(Path::new("")).join(name)
+    } else {
+      PathBuf::from(name)
+    };
+    let mut parts: Vec<String> = Vec::new();
+    for component in joined.components() {
+      match component {
+        Component::Normal(part) => parts.push(part.to_string_lossy().into_owned()),
+        Component::CurDir => (),
+        Component::ParentDir => {
+          if parts.pop().is_none() {
+            return Err(Error::new_resolving_message(
+              base,
+              name,
+              "path escapes the asset directory",
+            ));
+          }
+        }
+        Component::RootDir | Component::Prefix(_) => {
+          return Err(Error::new_resolving_message(
+            base,
+            name,
+            "absolute paths are not allowed",
+          ));
+        }
+      }
+    }
+    let mut resolved = parts.join("/");
+    if Path::new(&resolved).extension().is_none() {
+      resolved.push_str(".js");
+    }
+    Ok(resolved)
+  }
+}
+
+// reads modules from the directory `CommonAppHandler::get_file_path` puts "main.js" in,
+// that is the `assets` folder on desktop and the resource folder of the bundle on iOS
+pub struct AssetLoader {
+  root: PathBuf,
+}
+
+impl AssetLoader {
+  pub fn new(main_path: &str) -> Self {
+    Self {
+      root: asset_root(main_path),
+    }
+  }
+}
+
+pub fn asset_root(main_path: &str) -> PathBuf {
+  Path::new(main_path)
+    .parent()
+    .unwrap_or(Path::new(""))
+    .to_path_buf()
+}
+
+impl Loader for AssetLoader {
+  fn load<'js>(&mut self, _ctx: Ctx<'js>, name: &str) -> Result<ModuleData> {
+    match std::fs::read(self.root.join(name)) {
+      Ok(source) => Ok(ModuleData::source(name, source)),
+      Err(err) => Err(Error::new_loading_message(name, err.to_string())),
+    }
+  }
+}
@@ -1 +1,8 @@
+pub mod errors;
+pub mod gdx;
+pub mod gl;
+pub mod loader;
+pub mod overlay;
 pub mod rt;
+pub mod timers;
+pub mod watcher;
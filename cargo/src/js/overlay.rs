@@ -0,0 +1,247 @@
+use glow::*;
+use std::rc::Rc;
+
+use super::errors::JsError;
+use crate::{
+  gdx::g2d::{
+    batcher::{DrawOptions, PolygonBatch},
+    ortho_cam::OrthoCamera,
+    texture::{ImageData, PixelFormat, Texture, TextureOptions},
+  },
+  ScreenSize,
+};
+
+const GLYPH_WIDTH: usize = 5;
+const GLYPH_HEIGHT: usize = 7;
+// a pixel of spacing right of and two below every glyph
+const CELL_WIDTH: f32 = 6.;
+const CELL_HEIGHT: f32 = 9.;
+
+// the classic 5x7 LCD font for ASCII 32..=126, a byte per column, lowest bit on top
+const GLYPHS: [[u8; GLYPH_WIDTH]; 95] = [
+  [0x00, 0x00, 0x00, 0x00, 0x00], // space
+  [0x00, 0x00, 0x5F, 0x00, 0x00], // !
+  [0x00, 0x07, 0x00, 0x07, 0x00], // "
+  [0x14, 0x7F, 0x14, 0x7F, 0x14], // #
+  [0x24, 0x2A, 0x7F, 0x2A, 0x12], // $
+  [0x23, 0x13, 0x08, 0x64, 0x62], // %
+  [0x36, 0x49, 0x56, 0x20, 0x50], // &
+  [0x00, 0x00, 0x07, 0x00, 0x00], // '
+  [0x00, 0x1C, 0x22, 0x41, 0x00], // (
+  [0x00, 0x41, 0x22, 0x1C, 0x00], // )
+  [0x2A, 0x1C, 0x7F, 0x1C, 0x2A], // *
+  [0x08, 0x08, 0x3E, 0x08, 0x08], // +
+  [0x00, 0x50, 0x30, 0x00, 0x00], // ,
+  [0x08, 0x08, 0x08, 0x08, 0x08], // -
+  [0x00, 0x00, 0x60, 0x60, 0x00], // .
+  [0x20, 0x10, 0x08, 0x04, 0x02], // /
+  [0x3E, 0x51, 0x49, 0x45, 0x3E], // 0
+  [0x00, 0x42, 0x7F, 0x40, 0x00], // 1
+  [0x72, 0x49, 0x49, 0x49, 0x46], // 2
+  [0x21, 0x41, 0x49, 0x4D, 0x33], // 3
+  [0x18, 0x14, 0x12, 0x7F, 0x10], // 4
+  [0x27, 0x45, 0x45, 0x45, 0x39], // 5
+  [0x3C, 0x4A, 0x49, 0x49, 0x31], // 6
+  [0x41, 0x21, 0x11, 0x09, 0x07], // 7
+  [0x36, 0x49, 0x49, 0x49, 0x36], // 8
+  [0x46, 0x49, 0x49, 0x29, 0x1E], // 9
+  [0x00, 0x00, 0x14, 0x00, 0x00], // :
+  [0x00, 0x40, 0x34, 0x00, 0x00], // ;
+  [0x00, 0x08, 0x14, 0x22, 0x41], // <
+  [0x14, 0x14, 0x14, 0x14, 0x14], // =
+  [0x00, 0x41, 0x22, 0x14, 0x08], // >
+  [0x02, 0x01, 0x59, 0x09, 0x06], // ?
+  [0x3E, 0x41, 0x5D, 0x59, 0x4E], // @
+  [0x7C, 0x12, 0x11, 0x12, 0x7C], // A
+  [0x7F, 0x49, 0x49, 0x49, 0x36], // B
+  [0x3E, 0x41, 0x41, 0x41, 0x22], // C
+  [0x7F, 0x41, 0x41, 0x41, 0x3E], // D
+  [0x7F, 0x49, 0x49, 0x49, 0x41], // E
+  [0x7F, 0x09, 0x09, 0x09, 0x01], // F
+  [0x3E, 0x41, 0x41, 0x51, 0x73], // G
+  [0x7F, 0x08, 0x08, 0x08, 0x7F], // H
+  [0x00, 0x41, 0x7F, 0x41, 0x00], // I
+  [0x20, 0x40, 0x41, 0x3F, 0x01], // J
+  [0x7F, 0x08, 0x14, 0x22, 0x41], // K
+  [0x7F, 0x40, 0x40, 0x40, 0x40], // L
+  [0x7F, 0x02, 0x1C, 0x02, 0x7F], // M
+  [0x7F, 0x04, 0x08, 0x10, 0x7F], // N
+  [0x3E, 0x41, 0x41, 0x41, 0x3E], // O
+  [0x7F, 0x09, 0x09, 0x09, 0x06], // P
+  [0x3E, 0x41, 0x51, 0x21, 0x5E], // Q
+  [0x7F, 0x09, 0x19, 0x29, 0x46], // R
+  [0x26, 0x49, 0x49, 0x49, 0x32], // S
+  [0x03, 0x01, 0x7F, 0x01, 0x03], // T
+  [0x3F, 0x40, 0x40, 0x40, 0x3F], // U
+  [0x1F, 0x20, 0x40, 0x20, 0x1F], // V
+  [0x3F, 0x40, 0x38, 0x40, 0x3F], // W
+  [0x63, 0x14, 0x08, 0x14, 0x63], // X
+  [0x03, 0x04, 0x78, 0x04, 0x03], // Y
+  [0x61, 0x59, 0x49, 0x4D, 0x43], // Z
+  [0x00, 0x7F, 0x41, 0x41, 0x41], // [
+  [0x02, 0x04, 0x08, 0x10, 0x20], // \
+  [0x00, 0x41, 0x41, 0x41, 0x7F], // ]
+  [0x04, 0x02, 0x01, 0x02, 0x04], // ^
+  [0x40, 0x40, 0x40, 0x40, 0x40], // _
+  [0x00, 0x01, 0x02, 0x04, 0x00], // `
+  [0x20, 0x54, 0x54, 0x54, 0x78], // a
+  [0x7F, 0x28, 0x44, 0x44, 0x38], // b
+  [0x38, 0x44, 0x44, 0x44, 0x28], // c
+  [0x38, 0x44, 0x44, 0x28, 0x7F], // d
+  [0x38, 0x54, 0x54, 0x54, 0x18], // e
+  [0x00, 0x08, 0x7E, 0x09, 0x02], // f
+  [0x0C, 0x52, 0x52, 0x52, 0x3E], // g
+  [0x7F, 0x08, 0x04, 0x04, 0x78], // h
+  [0x00, 0x44, 0x7D, 0x40, 0x00], // i
+  [0x20, 0x40, 0x44, 0x3D, 0x00], // j
+  [0x7F, 0x10, 0x28, 0x44, 0x00], // k
+  [0x00, 0x41, 0x7F, 0x40, 0x00], // l
+  [0x7C, 0x04, 0x78, 0x04, 0x78], // m
+  [0x7C, 0x08, 0x04, 0x04, 0x78], // n
+  [0x38, 0x44, 0x44, 0x44, 0x38], // o
+  [0x7C, 0x14, 0x14, 0x14, 0x08], // p
+  [0x08, 0x14, 0x14, 0x18, 0x7C], // q
+  [0x7C, 0x08, 0x04, 0x04, 0x08], // r
+  [0x48, 0x54, 0x54, 0x54, 0x24], // s
+  [0x04, 0x04, 0x3F, 0x44, 0x24], // t
+  [0x3C, 0x40, 0x40, 0x20, 0x7C], // u
+  [0x1C, 0x20, 0x40, 0x20, 0x1C], // v
+  [0x3C, 0x40, 0x30, 0x40, 0x3C], // w
+  [0x44, 0x28, 0x10, 0x28, 0x44], // x
+  [0x0C, 0x50, 0x50, 0x50, 0x3C], // y
+  [0x44, 0x64, 0x54, 0x4C, 0x44], // z
+  [0x00, 0x08, 0x36, 0x41, 0x00], // {
+  [0x00, 0x00, 0x7F, 0x00, 0x00], // |
+  [0x00, 0x41, 0x36, 0x08, 0x00], // }
+  [0x08, 0x04, 0x08, 0x10, 0x08], // ~
+];
+
+// draws the error that paused the scripts, with its own batch and after resetting the
+// GL state a script may have left behind
+pub struct ErrorOverlay {
+  batch: PolygonBatch,
+  font: Rc<Texture>,
+  white: Rc<Texture>,
+}
+
+impl ErrorOverlay {
+  pub fn new(gl: &Rc<Context>) -> Self {
+    // all glyphs in one row, white on transparent so the batch color tints them
+    let width = GLYPHS.len() * GLYPH_WIDTH;
+    let mut pixels = vec![0; width * GLYPH_HEIGHT * 4];
+    for (index, glyph) in GLYPHS.iter().enumerate() {
+      for (column, bits) in glyph.iter().enumerate() {
+        for row in 0..GLYPH_HEIGHT {
+          if bits >> row & 1 == 1 {
+            let offset = (row * width + index * GLYPH_WIDTH + column) * 4;
+            pixels[offset..offset + 4].copy_from_slice(&[255; 4]);
+          }
+        }
+      }
+    }
+    let data = ImageData::new(
+      width as u32,
+      GLYPH_HEIGHT as u32,
+      PixelFormat::Rgba,
+      &pixels,
+    );
+    // the font is scaled up, linear filtering would blur it
+    let font = Texture::new_with_options(gl, data, &TextureOptions::nearest());
+    Self {
+      batch: PolygonBatch::create(gl),
+      font,
+      white: Texture::new_white_texture(gl),
+    }
+  }
+
+  pub fn draw(&mut self, gl: &Context, size: &ScreenSize, error: &JsError) {
+    let width = size.physical_width;
+    let height = size.physical_height;
+    unsafe {
+      gl.bind_framebuffer(FRAMEBUFFER, None);
+      gl.bind_vertex_array(None);
+      gl.viewport(0, 0, width as i32, height as i32);
+      gl.disable(SCISSOR_TEST);
+      gl.disable(DEPTH_TEST);
+      gl.disable(STENCIL_TEST);
+      gl.disable(CULL_FACE);
+      gl.color_mask(true, true, true, true);
+      gl.clear_color(0.2, 0.02, 0.04, 1.);
+      gl.clear(COLOR_BUFFER_BIT);
+    }
+    let mut camera = OrthoCamera::new(width, height, width, height);
+    camera.set_y_down(true);
+    camera.set_position(width / 2., height / 2.);
+
+    let scale = (2. * size.scale_factor).round().max(1.);
+    let margin = CELL_WIDTH * scale * 2.;
+    let columns = (((width - margin * 2.) / (CELL_WIDTH * scale)) as usize).max(1);
+    let rows = |text: &str| -> Vec<Vec<char>> {
+      let mut rows = vec![];
+      for line in text.lines() {
+        let chars: Vec<char> = line.replace('\t', "  ").chars().collect();
+        // long lines wrap, empty ones still take up a row
+        match chars.is_empty() {
+          true => rows.push(vec![]),
+          false => rows.extend(chars.chunks(columns).map(|row| row.to_vec())),
+        }
+      }
+      rows
+    };
+    let mut message = error.clone();
+    message.stack = None;
+    let mut lines = vec![([1., 0.85, 0.3], rows(&format!("{} failed", error.source)))];
+    lines.push(([1., 1., 1.], rows(&message.to_string())));
+    if let Some(ref stack) = error.stack {
+      lines.push(([0.7, 0.7, 0.7], rows(stack)));
+    }
+    // the empty line keeps the hint apart from the stack
+    lines.push(([1., 0.85, 0.3], rows("\nclick or tap to resume")));
+
+    self.batch.set_projection(&camera.combined);
+    self.batch.begin();
+    // a bar behind the title
+    self.batch.set_color_rgba(0.6, 0.05, 0.1, 1.);
+    let bar_height = margin + CELL_HEIGHT * scale * 1.5;
+    self.batch.draw(&self.white, 0., 0., width, bar_height);
+    let mut y = margin;
+    for (index, (color, rows)) in lines.iter().enumerate() {
+      self.batch.set_color_rgba(color[0], color[1], color[2], 1.);
+      for row in rows {
+        self.draw_text(row, margin, y, scale);
+        y += CELL_HEIGHT * scale;
+      }
+      // more space under the title
+      if index == 0 {
+        y = bar_height + margin / 2.;
+      }
+    }
+    self.batch.end();
+  }
+
+  fn draw_text(&mut self, text: &[char], x: f32, y: f32, scale: f32) {
+    let font_width = (GLYPHS.len() * GLYPH_WIDTH) as f32;
+    for (column, character) in text.iter().enumerate() {
+      let index = match *character as usize {
+        code @ 33..=126 => code - 32,
+        // spaces need no quad, anything outside ASCII shows as "?"
+        32 => continue,
+        _ => '?' as usize - 32,
+      };
+      let u1 = (index * GLYPH_WIDTH) as f32 / font_width;
+      let u2 = ((index + 1) * GLYPH_WIDTH) as f32 / font_width;
+      self.batch.draw_with_options(
+        &self.font,
+        x + column as f32 * CELL_WIDTH * scale,
+        y,
+        GLYPH_WIDTH as f32 * scale,
+        GLYPH_HEIGHT as f32 * scale,
+        &DrawOptions {
+          u1,
+          u2,
+          ..DrawOptions::default()
+        },
+      );
+    }
+  }
+}
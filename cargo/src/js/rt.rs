@@ -1,36 +1,68 @@
-use rquickjs::{bind, Context, Function, Object, Persistent, Runtime};
-use std::rc::Rc;
+use rquickjs::{
+  bind, function::AsArguments, Context, Error, Function, Object, Persistent, Runtime,
+};
+use std::{cell::RefCell, path::PathBuf, rc::Rc};
 
-use crate::CommonAppHandler;
+use super::{
+  errors::{ErrorPolicy, JsError, Rejections},
+  gdx::GdxJs,
+  gl::{glow_js, GlowJs},
+  loader::{asset_root, AssetLoader, AssetResolver},
+  overlay::ErrorOverlay,
+  timers::{init_timers, run_timers, Timers},
+  watcher::ScriptWatcher,
+};
+use crate::{
+  input::{Input, InputEvent},
+  CommonAppHandler, ScreenSize,
+};
+
+const MAIN_MODULE: &str = "main.js";
 
 #[bind(object)]
 #[quickjs(bare)]
-mod glow_js {
-  use glow::*;
-  use std::rc::Rc;
+mod input_js {
+  use std::{cell::RefCell, rc::Rc};
+
+  use crate::input::{Input, InputEvent};
 
-  pub struct JsContext {
+  pub struct JsInput {
     #[quickjs(skip)]
-    pub gl: Rc<Context>,
+    pub input: Rc<RefCell<Input>>,
   }
 
-  impl JsContext {
-    #[quickjs(proto)]
-    pub const COLOR_BUFFER_BIT: u32 = glow::COLOR_BUFFER_BIT;
+  impl JsInput {
     #[quickjs(skip)]
-    pub fn new(gl: &Rc<Context>) -> Self {
-      Self { gl: gl.clone() }
+    pub fn new(input: Rc<RefCell<Input>>) -> Self {
+      Self { input }
     }
-    pub fn clear(&self, mask: u32) {
-      unsafe {
-        self.gl.clear(mask);
-      }
+    // events queued since the previous frame, as `{ type: "pointerDown", pointer, button, x, y }` etc.
+    pub fn events(&self) -> Vec<InputEvent> {
+      self.input.borrow().events().to_vec()
     }
-    #[quickjs(rename = "clearColor")]
-    pub fn clear_color(&self, r: f32, g: f32, b: f32, a: f32) {
-      unsafe {
-        self.gl.clear_color(r, g, b, a);
-      }
+    #[quickjs(rename = "isKeyPressed")]
+    pub fn is_key_pressed(&self, key: String) -> bool {
+      self.input.borrow().is_key_pressed(&key)
+    }
+    #[quickjs(rename = "isPointerDown")]
+    pub fn is_pointer_down(&self, pointer: u32) -> bool {
+      self.input.borrow().is_pointer_down(pointer)
+    }
+    #[quickjs(rename = "getPointerX")]
+    pub fn get_pointer_x(&self, pointer: u32) -> f32 {
+      self.input.borrow().get_pointer_x(pointer)
+    }
+    #[quickjs(rename = "getPointerY")]
+    pub fn get_pointer_y(&self, pointer: u32) -> f32 {
+      self.input.borrow().get_pointer_y(pointer)
+    }
+    #[quickjs(rename = "getScrollX")]
+    pub fn get_scroll_x(&self) -> f32 {
+      self.input.borrow().get_scroll_x()
+    }
+    #[quickjs(rename = "getScrollY")]
+    pub fn get_scroll_y(&self) -> f32 {
+      self.input.borrow().get_scroll_y()
     }
   }
 }
@@ -43,48 +75,357 @@ fn print_obj(obj: Object) {
   println!("{:?}", obj);
 }
 
-pub fn init_js_runtime(app: &mut dyn CommonAppHandler, gl: &Rc<glow::Context>) {
-  let js_gl_context = glow_js::JsContext::new(gl);
-  let rt = Runtime::new().unwrap();
-  let ctx = Context::full(&rt).unwrap();
-  let update = ctx.with(|ctx| {
-    let _ = ctx.globals().init_def::<GlowJs>().unwrap();
-    let _ = ctx
-      .globals()
-      .set("print", Function::new(ctx.clone(), print).unwrap());
-    let _ = ctx
-      .globals()
-      .set("printObj", Function::new(ctx.clone(), print_obj).unwrap());
-
-    let start_module = ctx
-      .compile(
-        "main",
-        r#"
-    export const start = (gl) => {
-      gl.clearColor(1.0, .2, 0.4, 1.0);
-      let frame = 0;
-      // setTimeout(() => {
-      //   print('Hello from JS!');
-      // }, 1000);
-      return delta => {
-        frame++;
-        print(`Frame: ${frame}. Delta: ${delta}.`);
-        gl.clear(gl.COLOR_BUFFER_BIT);
+// the persistent handles are declared before `context`, struct fields drop in order
+// and every handle has to be released before the runtime is
+struct JsApp {
+  update: Persistent<Function<'static>>,
+  resize: Option<Persistent<Function<'static>>>,
+  pause: Option<Persistent<Function<'static>>>,
+  resume: Option<Persistent<Function<'static>>>,
+  dispose: Option<Persistent<Function<'static>>>,
+  // the live export namespace of main.js, handed to `migrate` of the next version
+  exports: Persistent<Object<'static>>,
+  // pending timers of this version, a reload starts with none
+  timers: Rc<RefCell<Timers>>,
+  // rejected promises returned by this version's callbacks
+  rejections: Rejections,
+  context: Context,
+}
+
+impl JsApp {
+  fn call<A>(
+    &self,
+    source: &str,
+    function: &Persistent<Function<'static>>,
+    args: A,
+  ) -> Result<(), JsError>
+  where
+    A: for<'js> AsArguments<'js>,
+  {
+    self.context.with(|ctx| {
+      function
+        .clone()
+        .restore(ctx)
+        .and_then(|function| {
+          let result = function.call(args)?;
+          self.rejections.watch(ctx, source, result)
+        })
+        .map_err(|error| JsError::new(ctx, source, error))
+    })
+  }
+
+  fn call_hook<A>(
+    &self,
+    source: &str,
+    hook: &Option<Persistent<Function<'static>>>,
+    args: A,
+  ) -> Result<(), JsError>
+  where
+    A: for<'js> AsArguments<'js>,
+  {
+    match hook {
+      Some(hook) => self.call(source, hook, args),
+      None => Ok(()),
+    }
+  }
+}
+
+// the timer callbacks are handles too, the globals that set them keep `timers` alive
+// until the context itself is freed
+impl Drop for JsApp {
+  fn drop(&mut self) {
+    self.timers.borrow_mut().clear();
+  }
+}
+
+// everything needed to evaluate main.js again, every load gets a fresh context
+// so the module cache starts empty, the runtime and its loader are shared
+struct JsScripts {
+  app: Option<JsApp>,
+  watcher: Option<ScriptWatcher>,
+  gl: Rc<glow::Context>,
+  assets: PathBuf,
+  input: Rc<RefCell<Input>>,
+  policy: ErrorPolicy,
+  // set once a script failed and the policy pauses, cleared by a successful reload, the app
+  // coming back to the foreground or a tap on the overlay
+  error: Option<JsError>,
+  // created the first time an error is drawn
+  overlay: Option<ErrorOverlay>,
+  size: ScreenSize,
+  rt: Runtime,
+}
+
+impl JsScripts {
+  fn load(&self, previous: Option<&JsApp>) -> Result<JsApp, JsError> {
+    let context = Context::full(&self.rt).unwrap();
+    let timers = Rc::new(RefCell::new(Timers::default()));
+    let rejections = Rejections::default();
+    context.with(|ctx| {
+      ctx.globals().init_def::<GlowJs>().unwrap();
+      ctx.globals().init_def::<GdxJs>().unwrap();
+      ctx.globals().init_def::<InputJs>().unwrap();
+      ctx
+        .globals()
+        .set("input", input_js::JsInput::new(Rc::clone(&self.input)))
+        .unwrap();
+      ctx
+        .globals()
+        .set("print", Function::new(ctx, print).unwrap())
+        .unwrap();
+      ctx
+        .globals()
+        .set("printObj", Function::new(ctx, print_obj).unwrap())
+        .unwrap();
+      init_timers(ctx, &timers).unwrap();
+
+      let load = || -> rquickjs::Result<JsApp> {
+        // a namespace import keeps export bindings live, `Module::get` would only copy them
+        let bootstrap = ctx.compile(
+          "<bootstrap>",
+          format!(
+            "import * as main from \"{}\"; export {{ main }};",
+            MAIN_MODULE
+          ),
+        )?;
+        let exports: Object = bootstrap.get("main")?;
+        let start: Function = exports.get("start")?;
+        let update: Function =
+          start.call((glow_js::JsContext::new(&self.gl, self.assets.clone()),))?;
+        // everything but `start` is optional
+        let hook = |name: &str| {
+          exports
+            .get::<_, Function>(name)
+            .ok()
+            .map(|hook| Persistent::save(ctx, hook))
+        };
+        if let (Some(previous), Ok(migrate)) = (previous, exports.get::<_, Function>("migrate")) {
+          let previous = previous.exports.clone().restore(ctx)?;
+          let result = migrate.call((previous,))?;
+          rejections.watch(ctx, "migrate", result)?;
+        }
+        Ok(JsApp {
+          update: Persistent::save(ctx, update),
+          resize: hook("resize"),
+          pause: hook("pause"),
+          resume: hook("resume"),
+          dispose: hook("dispose"),
+          exports: Persistent::save(ctx, exports.clone()),
+          timers: Rc::clone(&timers),
+          rejections: rejections.clone(),
+          context: context.clone(),
+        })
       };
+      load().map_err(|error| JsError::new(ctx, "load", error))
+    })
+  }
+
+  // checked between frames, a version that fails to load leaves the running one in place,
+  // one that loads clears the error that paused the previous version, the previous version's
+  // `dispose` runs once the new one has loaded and migrated so its GL objects don't leak
+  fn reload_if_changed(&mut self) {
+    let changed = match self.watcher {
+      Some(ref mut watcher) => watcher.poll(),
+      None => false,
+    };
+    if !changed {
+      return;
+    }
+    match self.load(self.app.as_ref()) {
+      Ok(app) => {
+        if let Some(ref previous) = self.app {
+          // the new version is already running, a failing `dispose` is only logged
+          if let Err(error) = previous.call_hook("dispose", &previous.dispose, ()) {
+            println!("js {} failed: {}", error.source, error);
+          }
+        }
+        println!("reloaded {}", MAIN_MODULE);
+        self.app = Some(app);
+        self.error = None;
+      }
+      Err(error) => self.report(error),
     }
-"#,
-      )
-      .unwrap();
-    unsafe {
-      let _ = start_module.eval().unwrap();
-      let start: Function = start_module.get("start").unwrap();
-      let update: Function = start.call::<_, Function>((js_gl_context,)).unwrap();
-      Persistent::save(ctx, update)
+  }
+
+  // the scripts run as they are again, the error is likely thrown again if nothing changed,
+  // a main.js that never loaded has nothing to resume
+  fn resume_after_error(&mut self) {
+    if self.app.is_none() {
+      return;
     }
-  });
+    if let Some(error) = self.error.take() {
+      println!("resuming js after {} failed", error.source);
+    }
+  }
+
+  // every error is logged, with `Pause` or `Overlay` the first one also stops the scripts
+  fn report(&mut self, error: JsError) {
+    println!("js {} failed: {}", error.source, error);
+    if self.policy != ErrorPolicy::Log && self.error.is_none() {
+      self.error = Some(error);
+    }
+  }
+
+  // timers, then `update`, promise jobs run after each so async code resolves within the frame,
+  // jobs can't run inside `Context::with`, it holds the runtime lock, an error skips the rest
+  // of the frame
+  fn update(&mut self, delta: f32) {
+    if let Some(ref error) = self.error {
+      if self.policy == ErrorPolicy::Overlay {
+        let overlay = self
+          .overlay
+          .get_or_insert_with(|| ErrorOverlay::new(&self.gl));
+        overlay.draw(&self.gl, &self.size, error);
+        // the tap ends with this frame, the scripts don't see it
+        let tapped = self
+          .input
+          .borrow()
+          .events()
+          .iter()
+          .any(|event| matches!(event, InputEvent::PointerUp { .. }));
+        if tapped {
+          self.resume_after_error();
+        }
+      }
+      return;
+    }
+    let errors = match self.app {
+      Some(ref app) => app
+        .context
+        .with(|ctx| run_timers(ctx, &app.timers, &app.rejections, delta)),
+      None => return,
+    };
+    let errors = errors.into_iter().chain(self.run_pending_jobs());
+    if self.report_all(errors) {
+      return;
+    }
+    let result = match self.app {
+      Some(ref app) => app.call("update", &app.update, (delta,)),
+      None => return,
+    };
+    match result {
+      Ok(()) => (),
+      Err(error) => return self.report(error),
+    }
+    let errors = self.run_pending_jobs();
+    self.report_all(errors);
+  }
+
+  // true if anything was reported
+  fn report_all(&mut self, errors: impl IntoIterator<Item = JsError>) -> bool {
+    let mut failed = false;
+    for error in errors {
+      self.report(error);
+      failed = true;
+    }
+    failed
+  }
+
+  // a rejection is only known once the jobs settling its promise ran
+  fn run_pending_jobs(&self) -> Vec<JsError> {
+    let mut errors = vec![];
+    loop {
+      match self.rt.execute_pending_job() {
+        Ok(true) => (),
+        Ok(false) => break,
+        // the job's context is the one the exception is pending in
+        Err(exception) => errors.push(
+          exception
+            .0
+            .with(|ctx| JsError::new(ctx, "promise job", Error::Exception)),
+        ),
+      }
+    }
+    if let Some(ref app) = self.app {
+      errors.extend(app.rejections.take());
+    }
+    errors
+  }
+
+  // hooks don't run while paused on an error
+  fn call_hook<A>(
+    &mut self,
+    source: &str,
+    hook: fn(&JsApp) -> &Option<Persistent<Function<'static>>>,
+    args: A,
+  ) where
+    A: for<'js> AsArguments<'js>,
+  {
+    if self.error.is_some() {
+      return;
+    }
+    let result = match self.app {
+      Some(ref app) => app.call_hook(source, hook(app), args),
+      None => Ok(()),
+    };
+    match result {
+      Ok(()) => (),
+      Err(error) => self.report(error),
+    }
+  }
+}
+
+pub fn init_js_runtime(
+  app: &mut dyn CommonAppHandler,
+  gl: &Rc<glow::Context>,
+  policy: ErrorPolicy,
+) {
+  let main_path = app.get_file_path(MAIN_MODULE);
+  let rt = Runtime::new().unwrap();
+  rt.set_loader(AssetResolver, AssetLoader::new(&main_path));
+  let mut scripts = JsScripts {
+    app: None,
+    watcher: None,
+    gl: Rc::clone(gl),
+    assets: asset_root(&main_path),
+    input: app.get_input(),
+    policy,
+    error: None,
+    overlay: None,
+    size: app.get_screen_size(),
+    rt,
+  };
+  match scripts.load(None) {
+    Ok(js_app) => scripts.app = Some(js_app),
+    Err(error) => scripts.report(error),
+  }
+  if app.is_hot_reload_enabled() {
+    scripts.watcher = Some(ScriptWatcher::new(scripts.assets.clone()));
+  } else if scripts.app.is_none() && policy != ErrorPolicy::Overlay {
+    return;
+  }
+  let scripts = Rc::new(RefCell::new(scripts));
+
+  let scripts_ref = Rc::clone(&scripts);
+  app.set_resize_fn(Box::new(move |_gl, size| {
+    let mut scripts = scripts_ref.borrow_mut();
+    scripts.size = *size;
+    scripts.call_hook("resize", |app| &app.resize, (*size,));
+  }));
+  let scripts_ref = Rc::clone(&scripts);
+  app.set_pause_fn(Box::new(move |_gl| {
+    scripts_ref
+      .borrow_mut()
+      .call_hook("pause", |app| &app.pause, ());
+  }));
+  let scripts_ref = Rc::clone(&scripts);
+  app.set_resume_fn(Box::new(move |_gl| {
+    let mut scripts = scripts_ref.borrow_mut();
+    // without a watcher, e.g. on iOS, this is how a paused app gets going again
+    scripts.resume_after_error();
+    scripts.call_hook("resume", |app| &app.resume, ());
+  }));
+  let scripts_ref = Rc::clone(&scripts);
+  app.set_dispose_fn(Box::new(move |_gl| {
+    let mut scripts = scripts_ref.borrow_mut();
+    // a paused script still gets to release what it holds
+    scripts.error = None;
+    scripts.call_hook("dispose", |app| &app.dispose, ());
+    scripts.overlay = None;
+  }));
   app.set_update_fn(Box::new(move |_gl, delta| {
-    ctx.with(|ctx| {
-      let _: () = update.clone().restore(ctx).unwrap().call((delta,)).unwrap();
-    });
+    let mut scripts = scripts.borrow_mut();
+    scripts.reload_if_changed();
+    scripts.update(delta);
   }));
 }
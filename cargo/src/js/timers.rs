@@ -0,0 +1,177 @@
+use rquickjs::{
+  function::{Opt, Rest},
+  Ctx, Function, Persistent, Result, Value,
+};
+use std::{cell::RefCell, rc::Rc};
+
+use super::errors::{JsError, Rejections};
+
+type Callback = Persistent<Function<'static>>;
+type Arguments = Vec<Persistent<Value<'static>>>;
+
+struct Timer {
+  id: u32,
+  due: f64,
+  // only set for setInterval
+  interval: Option<f64>,
+  callback: Callback,
+  args: Arguments,
+}
+
+// setTimeout and friends run on game time, the clock only moves by the frame deltas so
+// timers hold still while the app is paused, times are milliseconds like in browsers
+#[derive(Default)]
+pub struct Timers {
+  now: f64,
+  last_id: u32,
+  timers: Vec<Timer>,
+  animation_frames: Vec<(u32, Callback)>,
+}
+
+impl Timers {
+  fn next_id(&mut self) -> u32 {
+    self.last_id += 1;
+    self.last_id
+  }
+
+  fn add(&mut self, callback: Callback, delay: f64, repeat: bool, args: Arguments) -> u32 {
+    let id = self.next_id();
+    // negative and NaN delays count as 0 like in browsers
+    let delay = if delay > 0. { delay } else { 0. };
+    self.timers.push(Timer {
+      id,
+      due: self.now + delay,
+      interval: if repeat { Some(delay) } else { None },
+      callback,
+      args,
+    });
+    id
+  }
+
+  fn request_animation_frame(&mut self, callback: Callback) -> u32 {
+    let id = self.next_id();
+    self.animation_frames.push((id, callback));
+    id
+  }
+
+  // timers and animation frames share ids, so any clear function cancels either
+  fn remove(&mut self, id: u32) {
+    self.timers.retain(|timer| timer.id != id);
+    self
+      .animation_frames
+      .retain(|(frame_id, _)| *frame_id != id);
+  }
+
+  // every callback has to be released before the context they belong to
+  pub fn clear(&mut self) {
+    self.timers.clear();
+    self.animation_frames.clear();
+  }
+
+  // ids of the timers that are due, earliest first
+  fn due(&self) -> Vec<u32> {
+    let mut due: Vec<&Timer> = self
+      .timers
+      .iter()
+      .filter(|timer| timer.due <= self.now)
+      .collect();
+    due.sort_by(|a, b| a.due.total_cmp(&b.due).then(a.id.cmp(&b.id)));
+    due.iter().map(|timer| timer.id).collect()
+  }
+
+  // a timeout is gone once it fired, an interval moves on to its next due time but never
+  // into the past, so a late or 0ms interval fires once per frame instead of catching up
+  fn fire(&mut self, id: u32) -> Option<(Callback, Arguments)> {
+    let index = self.timers.iter().position(|timer| timer.id == id)?;
+    let now = self.now;
+    let timer = &mut self.timers[index];
+    match timer.interval {
+      Some(interval) => {
+        timer.due = (timer.due + interval).max(now);
+        Some((timer.callback.clone(), timer.args.clone()))
+      }
+      None => {
+        let timer = self.timers.remove(index);
+        Some((timer.callback, timer.args))
+      }
+    }
+  }
+}
+
+pub fn init_timers<'js>(ctx: Ctx<'js>, timers: &Rc<RefCell<Timers>>) -> Result<()> {
+  let globals = ctx.globals();
+  for (name, repeat) in [("setTimeout", false), ("setInterval", true)] {
+    let timers = Rc::clone(timers);
+    let set =
+      move |ctx: Ctx<'js>, callback: Function<'js>, delay: Opt<f64>, args: Rest<Value<'js>>| {
+        let args = args.0.into_iter().map(|arg| Persistent::save(ctx, arg));
+        timers.borrow_mut().add(
+          Persistent::save(ctx, callback),
+          delay.0.unwrap_or(0.),
+          repeat,
+          args.collect(),
+        )
+      };
+    globals.set(name, Function::new(ctx, set)?)?;
+  }
+  let timers_ref = Rc::clone(timers);
+  let request = move |ctx: Ctx<'js>, callback: Function<'js>| {
+    timers_ref
+      .borrow_mut()
+      .request_animation_frame(Persistent::save(ctx, callback))
+  };
+  globals.set("requestAnimationFrame", Function::new(ctx, request)?)?;
+  for name in ["clearTimeout", "clearInterval", "cancelAnimationFrame"] {
+    let timers = Rc::clone(timers);
+    let clear = move |id: Opt<Option<u32>>| {
+      if let Some(id) = id.0.flatten() {
+        timers.borrow_mut().remove(id);
+      }
+    };
+    globals.set(name, Function::new(ctx, clear)?)?;
+  }
+  Ok(())
+}
+
+// advances the clock by one frame, then runs the due timers and the animation frames
+// requested before this frame, a callback that throws doesn't stop the rest, its error is
+// returned with the others, async callbacks are watched by `rejections`
+pub fn run_timers<'js>(
+  ctx: Ctx<'js>,
+  timers: &Rc<RefCell<Timers>>,
+  rejections: &Rejections,
+  delta: f32,
+) -> Vec<JsError> {
+  let mut errors = vec![];
+  timers.borrow_mut().now += delta as f64 * 1000.;
+  let due = timers.borrow().due();
+  for id in due {
+    // an earlier callback may have cleared this one, the borrow has to end before it runs
+    let fired = timers.borrow_mut().fire(id);
+    if let Some((callback, args)) = fired {
+      let result = callback.restore(ctx).and_then(|callback| {
+        let args = args
+          .into_iter()
+          .map(|arg| arg.restore(ctx))
+          .collect::<Result<Vec<_>>>()?;
+        let result = callback.call((Rest(args),))?;
+        rejections.watch(ctx, "timer", result)
+      });
+      if let Err(error) = result {
+        errors.push(JsError::new(ctx, "timer", error));
+      }
+    }
+  }
+  let frames = std::mem::take(&mut timers.borrow_mut().animation_frames);
+  let now = timers.borrow().now;
+  for (_, callback) in frames {
+    let result = callback.restore(ctx).and_then(|callback| {
+      let result = callback.call((now,))?;
+      rejections.watch(ctx, "animation frame", result)
+    });
+    if let Err(error) = result {
+      errors.push(JsError::new(ctx, "animation frame", error));
+    }
+  }
+  errors
+}
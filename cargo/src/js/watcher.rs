@@ -0,0 +1,69 @@
+use std::{
+  collections::HashMap,
+  path::{Path, PathBuf},
+  time::{Duration, Instant, SystemTime},
+};
+
+// a stat() per script every half second is cheap enough for a dev loop
+// and doesn't need a file notification dependency on every desktop platform
+const CHECK_INTERVAL: Duration = Duration::from_millis(500);
+
+pub struct ScriptWatcher {
+  root: PathBuf,
+  modified: HashMap<PathBuf, SystemTime>,
+  last_check: Instant,
+}
+
+impl ScriptWatcher {
+  pub fn new(root: PathBuf) -> Self {
+    let modified = scan(&root);
+    Self {
+      root,
+      modified,
+      last_check: Instant::now(),
+    }
+  }
+
+  // true when a script was saved, added or removed since the last call
+  pub fn poll(&mut self) -> bool {
+    if self.last_check.elapsed() < CHECK_INTERVAL {
+      return false;
+    }
+    self.last_check = Instant::now();
+    let modified = scan(&self.root);
+    if modified == self.modified {
+      return false;
+    }
+    self.modified = modified;
+    true
+  }
+}
+
+fn scan(root: &Path) -> HashMap<PathBuf, SystemTime> {
+  let mut modified = HashMap::new();
+  let mut dirs = vec![root.to_path_buf()];
+  while let Some(dir) = dirs.pop() {
+    let entries = match std::fs::read_dir(&dir) {
+      Ok(entries) => entries,
+      Err(_) => continue,
+    };
+    for entry in entries.flatten() {
+      let path = entry.path();
+      let metadata = match entry.metadata() {
+        Ok(metadata) => metadata,
+        Err(_) => continue,
+      };
+      if metadata.is_dir() {
+        dirs.push(path);
+      } else if path
+        .extension()
+        .map_or(false, |extension| extension == "js")
+      {
+        if let Ok(time) = metadata.modified() {
+          modified.insert(path, time);
+        }
+      }
+    }
+  }
+  modified
+}
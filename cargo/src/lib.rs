@@ -1,13 +1,11 @@
-use gdx::{
-  g2d::{batcher::PolygonBatch, texture::Texture},
-  misc::frame_counter::FrameCounter,
-};
 use glow::*;
-use js::rt::init_js_runtime;
-use rand::Rng;
-use std::rc::Rc;
+use input::Input;
+use js::{errors::ErrorPolicy, rt::init_js_runtime};
+use rquickjs::IntoJs;
+use std::{cell::RefCell, rc::Rc};
 
 pub mod gdx;
+pub mod input;
 pub mod js;
 
 #[cfg(target_os = "ios")]
@@ -16,19 +14,178 @@ pub mod ios;
 #[cfg(not(target_os = "ios"))]
 pub mod glutin;
 
-#[derive(Debug)]
-struct Sprite {
-  x: f32,
-  y: f32,
-  speed_x: f32,
-  speed_y: f32,
+#[cfg(target_os = "linux")]
+pub mod linux;
+
+#[cfg(target_os = "linux")]
+pub mod headless;
+
+// width/height are logical points, the physical size is what the framebuffer has
+#[derive(Debug, Clone, Copy, PartialEq, IntoJs)]
+#[quickjs(rename_all = "camelCase")]
+pub struct ScreenSize {
+  pub width: f32,
+  pub height: f32,
+  pub physical_width: f32,
+  pub physical_height: f32,
+  pub scale_factor: f32,
+}
+
+impl ScreenSize {
+  pub fn from_logical(width: f32, height: f32, scale_factor: f32) -> Self {
+    Self {
+      width,
+      height,
+      physical_width: (width * scale_factor).round(),
+      physical_height: (height * scale_factor).round(),
+      scale_factor,
+    }
+  }
+
+  pub fn from_physical(physical_width: f32, physical_height: f32, scale_factor: f32) -> Self {
+    Self {
+      width: physical_width / scale_factor,
+      height: physical_height / scale_factor,
+      physical_width,
+      physical_height,
+      scale_factor,
+    }
+  }
 }
 
 pub trait CommonAppHandler {
   fn set_update_fn(&mut self, update: Box<dyn FnMut(&Rc<Context>, f32) -> () + 'static>);
-  fn get_width(&self) -> f32;
-  fn get_height(&self) -> f32;
+  // called after the backend has reset the viewport to the new physical size
+  fn set_resize_fn(&mut self, resize: Box<dyn FnMut(&Rc<Context>, &ScreenSize) -> () + 'static>);
+  // the app lost the foreground (home button, window lost focus), save state here
+  fn set_pause_fn(&mut self, pause: Box<dyn FnMut(&Rc<Context>) -> () + 'static>);
+  fn set_resume_fn(&mut self, resume: Box<dyn FnMut(&Rc<Context>) -> () + 'static>);
+  // runs once on exit while the GL context is still current, every other callback is
+  // dropped right after so GL resources they own are released before the context goes away
+  fn set_dispose_fn(&mut self, dispose: Box<dyn FnOnce(&Rc<Context>) -> () + 'static>);
+  fn get_screen_size(&self) -> ScreenSize;
+  fn get_width(&self) -> f32 {
+    self.get_screen_size().width
+  }
+  fn get_height(&self) -> f32 {
+    self.get_screen_size().height
+  }
+  fn get_physical_width(&self) -> f32 {
+    self.get_screen_size().physical_width
+  }
+  fn get_physical_height(&self) -> f32 {
+    self.get_screen_size().physical_height
+  }
+  fn get_scale_factor(&self) -> f32 {
+    self.get_screen_size().scale_factor
+  }
   fn get_file_path(&self, file_name: &str) -> String;
+  fn get_input(&self) -> Rc<RefCell<Input>>;
+  // backends running from a source checkout pick up edited scripts without a restart
+  fn is_hot_reload_enabled(&self) -> bool {
+    false
+  }
+}
+
+// callback storage shared by the backends
+#[derive(Default)]
+pub struct AppCallbacks {
+  update: Option<Box<dyn FnMut(&Rc<Context>, f32) -> ()>>,
+  resize: Option<Box<dyn FnMut(&Rc<Context>, &ScreenSize) -> ()>>,
+  pause: Option<Box<dyn FnMut(&Rc<Context>) -> ()>>,
+  resume: Option<Box<dyn FnMut(&Rc<Context>) -> ()>>,
+  dispose: Option<Box<dyn FnOnce(&Rc<Context>) -> ()>>,
+  paused: bool,
+  disposed: bool,
+}
+
+impl AppCallbacks {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn set_update_fn(&mut self, update: Box<dyn FnMut(&Rc<Context>, f32) -> () + 'static>) {
+    self.update = Some(update);
+  }
+
+  pub fn set_resize_fn(
+    &mut self,
+    resize: Box<dyn FnMut(&Rc<Context>, &ScreenSize) -> () + 'static>,
+  ) {
+    self.resize = Some(resize);
+  }
+
+  pub fn set_pause_fn(&mut self, pause: Box<dyn FnMut(&Rc<Context>) -> () + 'static>) {
+    self.pause = Some(pause);
+  }
+
+  pub fn set_resume_fn(&mut self, resume: Box<dyn FnMut(&Rc<Context>) -> () + 'static>) {
+    self.resume = Some(resume);
+  }
+
+  pub fn set_dispose_fn(&mut self, dispose: Box<dyn FnOnce(&Rc<Context>) -> () + 'static>) {
+    self.dispose = Some(dispose);
+  }
+
+  pub fn is_paused(&self) -> bool {
+    self.paused
+  }
+
+  pub fn is_disposed(&self) -> bool {
+    self.disposed
+  }
+
+  pub fn update(&mut self, gl: &Rc<Context>, delta: f32) {
+    match self.update {
+      Some(ref mut update) => update(gl, delta),
+      None => (),
+    }
+  }
+
+  pub fn resize(&mut self, gl: &Rc<Context>, size: &ScreenSize) {
+    match self.resize {
+      Some(ref mut resize) => resize(gl, size),
+      None => (),
+    }
+  }
+
+  pub fn pause(&mut self, gl: &Rc<Context>) {
+    if self.paused || self.disposed {
+      return;
+    }
+    self.paused = true;
+    match self.pause {
+      Some(ref mut pause) => pause(gl),
+      None => (),
+    }
+  }
+
+  pub fn resume(&mut self, gl: &Rc<Context>) {
+    if !self.paused || self.disposed {
+      return;
+    }
+    self.paused = false;
+    match self.resume {
+      Some(ref mut resume) => resume(gl),
+      None => (),
+    }
+  }
+
+  pub fn dispose(&mut self, gl: &Rc<Context>) {
+    if self.disposed {
+      return;
+    }
+    self.pause(gl);
+    self.disposed = true;
+    match self.dispose.take() {
+      Some(dispose) => dispose(gl),
+      None => (),
+    }
+    self.update = None;
+    self.resize = None;
+    self.pause = None;
+    self.resume = None;
+  }
 }
 
 pub fn init_game<F>(init_func: F)
@@ -41,7 +198,13 @@ where
     ios::start_ios(init_func);
   }
 
-  #[cfg(not(target_os = "ios"))]
+  #[cfg(target_os = "linux")]
+  match headless::HeadlessOptions::from_env() {
+    Some(options) => headless::start_headless(options, init_func),
+    None => linux::start_linux(init_func),
+  }
+
+  #[cfg(not(any(target_os = "ios", target_os = "linux")))]
   glutin::start_glutin(init_func);
 }
 
@@ -49,76 +212,9 @@ where
 pub extern "C" fn start_app() {
   unsafe {
     init_game(|app, gl| {
-      init_js_runtime(app, gl);
-      // let width = app.get_width();
-      // let height = app.get_height();
-      // let mut batch = PolygonBatch::create(&gl);
-      // batch.set_y_down(true);
-
-      // let mut camera = crate::gdx::g2d::ortho_cam::OrthoCamera::new(width, height, width, height);
-      // camera.set_position(width / 2., height / 2.);
-      // camera.set_y_down(true);
-      // camera.update();
-
-      // let fox = Texture::load_from_file(&gl, app.get_file_path("fox.png"));
-
-      // gl.clear_color(0.5, 0.8, 0.2, 1.);
-
-      // let mut sprites = Vec::<Sprite>::new();
-      // let mut rng = rand::thread_rng();
-
-      // for _i in 0..40000 {
-      //   sprites.push(Sprite {
-      //     x: rng.gen::<f32>() * width,
-      //     y: rng.gen::<f32>() * height,
-      //     speed_x: rng.gen::<f32>() * width - width / 2.,
-      //     speed_y: rng.gen::<f32>() * height - height / 2.,
-      //   });
-      // }
-
-      // let draw_width = 20.;
-      // let draw_height = 20.;
-      // let mut accumulate = 0.;
-      // let mut frame_counter = FrameCounter::new();
-
-      // app.set_update_fn(Box::new(move |gl, delta| {
-      //   accumulate += delta;
-      //   frame_counter.update();
-      //   if accumulate >= 1. {
-      //     println!("fps: {}", frame_counter.fps());
-      //     accumulate = 0.;
-      //   }
-
-      //   gl.clear(COLOR_BUFFER_BIT);
-      //   batch.set_projection(&camera.combined);
-      //   batch.begin();
-      //   for sprite in &mut sprites {
-      //     sprite.x += sprite.speed_x * delta;
-      //     sprite.y += sprite.speed_y * delta;
-      //     if sprite.x >= width {
-      //       sprite.x = width;
-      //       sprite.speed_x = -sprite.speed_x;
-      //     } else if sprite.x <= 0. {
-      //       sprite.x = 0.;
-      //       sprite.speed_x = -sprite.speed_x;
-      //     }
-      //     if sprite.y >= height {
-      //       sprite.y = height;
-      //       sprite.speed_y = -sprite.speed_y;
-      //     } else if sprite.y <= 0. {
-      //       sprite.y = 0.;
-      //       sprite.speed_y = -sprite.speed_y;
-      //     }
-      //     batch.draw(
-      //       &fox,
-      //       sprite.x - draw_width / 2.,
-      //       sprite.y - draw_height / 2.,
-      //       draw_width,
-      //       draw_height,
-      //     );
-      //   }
-      //   batch.end();
-      // }));
+      init_js_runtime(app, gl, ErrorPolicy::default());
+      // the 40,000 sprite benchmark is assets/benchmark.js, `export * from "./benchmark.js";`
+      // as the whole main.js runs it
     });
   }
 }
@@ -0,0 +1,72 @@
+use glow::*;
+use glutin::{
+  event_loop::EventLoop,
+  platform::unix::{EventLoopWindowTargetExtUnix, WindowBuilderExtUnix},
+  window::{Window, WindowBuilder},
+  Api, ContextBuilder, ContextWrapper, GlRequest, PossiblyCurrent,
+};
+use std::rc::Rc;
+
+use crate::{
+  glutin::{run_event_loop, GlutinAppHandler},
+  CommonAppHandler,
+};
+
+const APP_ID: &str = "my_game";
+
+fn window_builder() -> WindowBuilder {
+  WindowBuilder::new()
+    .with_title("Hello triangle!")
+    .with_inner_size(glutin::dpi::LogicalSize::new(325.0, 768.0))
+    // wayland compositors group windows by app id, x11 window managers by WM_CLASS
+    .with_app_id(APP_ID.to_string())
+    .with_class(APP_ID.to_string(), APP_ID.to_string())
+}
+
+// our shaders are written against `#version 100`, so ask for a GLES 3 context first
+// (same as iOS) and only fall back to whatever desktop GL the driver offers
+fn create_window(event_loop: &EventLoop<()>) -> ContextWrapper<PossiblyCurrent, Window> {
+  let requests = [
+    GlRequest::Specific(Api::OpenGlEs, (3, 0)),
+    GlRequest::Latest,
+  ];
+  let mut last_error = None;
+  for request in requests {
+    let window = ContextBuilder::new()
+      .with_vsync(true)
+      .with_gl(request)
+      .build_windowed(window_builder(), event_loop);
+    match window {
+      Ok(window) => unsafe {
+        return window.make_current().unwrap();
+      },
+      Err(err) => last_error = Some(err),
+    }
+  }
+  panic!("failed to create a GL context: {:?}", last_error.unwrap());
+}
+
+// winit picks wayland when WAYLAND_DISPLAY is set and falls back to x11,
+// WINIT_UNIX_BACKEND=x11|wayland forces one of them
+pub fn start_linux<F>(init_func: F)
+where
+  F: FnOnce(&mut dyn CommonAppHandler, &Rc<Context>) -> () + 'static,
+{
+  let event_loop = EventLoop::new();
+  let window = create_window(&event_loop);
+  println!(
+    "start linux ({})",
+    if event_loop.is_wayland() {
+      "wayland"
+    } else {
+      "x11"
+    }
+  );
+  let gl =
+    unsafe { glow::Context::from_loader_function(|s| window.get_proc_address(s) as *const _) };
+
+  let gl = Rc::new(gl);
+  let mut app = GlutinAppHandler::new(window.window());
+  init_func(&mut app, &gl);
+  run_event_loop(gl, window, event_loop, app);
+}
@@ -0,0 +1,174 @@
+// renders through the OSMesa backend, what CI without a GPU runs, libOSMesa has to be installed
+// so the tests are ignored by default, CI runs them with `cargo test -- --ignored`
+#![cfg(target_os = "linux")]
+
+use std::{fs, rc::Rc};
+
+use my_game::{
+  gdx::g2d::{
+    batcher::PolygonBatch,
+    texture::{ImageData, PixelFormat, Texture, TextureOptions},
+  },
+  headless::{run_headless, HeadlessOptions},
+  js::{errors::ErrorPolicy, rt::init_js_runtime},
+};
+
+const SIZE: u32 = 16;
+
+// `pixels` comes from `run_headless`, bottom row first
+fn pixel(pixels: &[u8], x: u32, y: u32) -> [u8; 4] {
+  let i = ((y * SIZE + x) * 4) as usize;
+  [pixels[i], pixels[i + 1], pixels[i + 2], pixels[i + 3]]
+}
+
+#[test]
+#[ignore = "needs libOSMesa"]
+fn batch_draws_into_the_framebuffer() {
+  let options = HeadlessOptions {
+    width: SIZE,
+    height: SIZE,
+    frames: 2,
+    ..HeadlessOptions::default()
+  };
+  let pixels = run_headless(options, |app, gl| {
+    let red = Rc::new(Texture::new_with_options(
+      gl,
+      ImageData::new(1, 1, PixelFormat::Rgba, &[255, 0, 0, 255]),
+      &TextureOptions::nearest(),
+    ));
+    let mut batch = PolygonBatch::create(gl);
+    // pixel coordinates, y up
+    let mut projection = [0.0; 16];
+    projection[0] = 2.0 / SIZE as f32;
+    projection[5] = 2.0 / SIZE as f32;
+    projection[10] = 1.0;
+    projection[12] = -1.0;
+    projection[13] = -1.0;
+    projection[15] = 1.0;
+    batch.set_projection(&projection);
+    app.set_update_fn(Box::new(move |gl, _delta| {
+      unsafe {
+        use glow::HasContext;
+        gl.clear_color(0.0, 0.0, 1.0, 1.0);
+        gl.clear(glow::COLOR_BUFFER_BIT);
+      }
+      batch.begin();
+      batch.draw(&red, 0.0, 0.0, 8.0, 8.0);
+      batch.end();
+    }));
+  });
+  assert_eq!(pixel(&pixels, 1, 1), [255, 0, 0, 255]);
+  assert_eq!(pixel(&pixels, 7, 7), [255, 0, 0, 255]);
+  assert_eq!(pixel(&pixels, 8, 1), [0, 0, 255, 255]);
+  assert_eq!(pixel(&pixels, 1, 8), [0, 0, 255, 255]);
+}
+
+// runs `script` as main.js for `frames` frames of 0.5 seconds, `test` keeps the asset
+// directories of tests running in parallel apart
+fn run_script(test: &str, script: &str, frames: u32, policy: ErrorPolicy) -> Vec<u8> {
+  let assets =
+    std::env::temp_dir().join(format!("my_game_headless_{}_{}", test, std::process::id()));
+  fs::create_dir_all(&assets).unwrap();
+  fs::write(assets.join("main.js"), script).unwrap();
+  let options = HeadlessOptions {
+    width: SIZE,
+    height: SIZE,
+    frames,
+    delta: 0.5,
+    assets: assets.to_string_lossy().into_owned(),
+  };
+  let pixels = run_headless(options, |app, gl| init_js_runtime(app, gl, policy));
+  fs::remove_dir_all(&assets).unwrap();
+  pixels
+}
+
+#[test]
+#[ignore = "needs libOSMesa"]
+fn js_start_and_update_run() {
+  // green only once update ran every frame with the fixed delta
+  let pixels = run_script(
+    "update",
+    r#"
+export const start = (gl) => {
+  let frames = 0;
+  return (delta) => {
+    frames++;
+    const green = frames === 3 && Math.abs(delta - 0.5) < 1e-6 ? 1 : 0;
+    gl.clearColor(0, green, 1, 1);
+    gl.clear(gl.COLOR_BUFFER_BIT);
+  };
+};
+"#,
+    3,
+    ErrorPolicy::Log,
+  );
+  assert_eq!(pixel(&pixels, 0, 0), [0, 255, 255, 255]);
+  assert_eq!(pixel(&pixels, SIZE - 1, SIZE - 1), [0, 255, 255, 255]);
+}
+
+#[test]
+#[ignore = "needs libOSMesa"]
+fn js_calls_that_would_read_out_of_bounds_throw() {
+  // green only if every call that would make GL read past its data threw
+  let pixels = run_script(
+    "bounds",
+    r#"
+export const start = (gl) => {
+  const throws = (call) => {
+    try {
+      call();
+      return false;
+    } catch (error) {
+      return true;
+    }
+  };
+  gl.bindTexture(gl.TEXTURE_2D, gl.createTexture());
+  const upload = (bytes) => () =>
+    gl.texImage2D(gl.TEXTURE_2D, 0, gl.RGB, 1, 2, 0, gl.RGB, gl.UNSIGNED_BYTE, new Uint8Array(bytes));
+  const checks = [
+    // 1x2 RGB rows are padded to 4 bytes, the first row included
+    throws(upload(6)),
+    !throws(upload(7)),
+    throws(() => gl.vertexAttrib4fv(0, [1])),
+    throws(() => gl.vertexAttribPointer(0, 2, gl.FLOAT, false, 0, 0)),
+    throws(() => gl.drawElements(gl.TRIANGLES, 3, gl.UNSIGNED_SHORT, 0)),
+  ];
+  gl.enableVertexAttribArray(0);
+  checks.push(throws(() => gl.drawArrays(gl.TRIANGLES, 0, 3)));
+  gl.disableVertexAttribArray(0);
+  const green = checks.every((check) => check) ? 1 : 0;
+  return () => {
+    gl.clearColor(0, green, 1, 1);
+    gl.clear(gl.COLOR_BUFFER_BIT);
+  };
+};
+"#,
+    1,
+    ErrorPolicy::Log,
+  );
+  assert_eq!(pixel(&pixels, 0, 0), [0, 255, 255, 255]);
+}
+
+#[test]
+#[ignore = "needs libOSMesa"]
+fn js_async_rejections_pause_the_scripts() {
+  // the first frame draws green, then rejects, any frame after it would draw red
+  let pixels = run_script(
+    "async",
+    r#"
+export const start = (gl) => {
+  let frames = 0;
+  return async () => {
+    frames++;
+    gl.clearColor(frames === 1 ? 0 : 1, frames === 1 ? 1 : 0, 0, 1);
+    gl.clear(gl.COLOR_BUFFER_BIT);
+    await null;
+    throw new Error("rejected");
+  };
+};
+"#,
+    3,
+    ErrorPolicy::Pause,
+  );
+  assert_eq!(pixel(&pixels, 0, 0), [0, 255, 0, 255]);
+}
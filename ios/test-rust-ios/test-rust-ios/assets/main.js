@@ -0,0 +1,34 @@
+import { BACKGROUND } from "./colors.js";
+
+// module state lives in exported objects so `migrate` can carry it over a hot reload
+export const state = { frame: 0 };
+
+export const start = (gl) => {
+  gl.clearColor(...BACKGROUND);
+  setTimeout(() => {
+    print("Hello from JS!");
+  }, 1000);
+  return (delta) => {
+    state.frame++;
+    for (const event of input.events()) {
+      if (event.type === "pointerDown") {
+        print(`Pointer ${event.pointer} down at ${event.x}, ${event.y}.`);
+      }
+    }
+    print(`Frame: ${state.frame}. Delta: ${delta}.`);
+    gl.clear(gl.COLOR_BUFFER_BIT);
+  };
+};
+
+// called on the new version after its `start`, with the exports of the version it replaces
+export const migrate = (previous) => {
+  Object.assign(state, previous.state);
+};
+
+export const resize = (size) => {
+  print(`Resized to ${size.width}x${size.height} @${size.scaleFactor}x.`);
+};
+
+export const pause = () => print("Paused.");
+export const resume = () => print("Resumed.");
+export const dispose = () => print("Disposed.");